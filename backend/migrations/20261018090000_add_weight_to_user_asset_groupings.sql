@@ -0,0 +1,30 @@
+-- Add migration script here
+ALTER TABLE user_asset_groupings ADD COLUMN weight TEXT NOT NULL DEFAULT '100';
+
+-- Before weights, repeating a (account, asset, category) row said nothing the first one did
+-- not, so merge duplicates into the oldest row to let the unique index below be created.
+DELETE FROM user_asset_groupings
+WHERE EXISTS (
+    SELECT 1
+    FROM user_asset_groupings AS kept
+    WHERE kept.account_id = user_asset_groupings.account_id
+        AND kept.asset_master_id = user_asset_groupings.asset_master_id
+        AND kept.category_id = user_asset_groupings.category_id
+        AND (
+            kept.created_at < user_asset_groupings.created_at
+            OR (kept.created_at = user_asset_groupings.created_at AND kept.id < user_asset_groupings.id)
+        )
+);
+
+-- An asset grouped into several categories used to count fully in each of them. Split it
+-- evenly instead, rounding down to 2 decimal places so no asset exceeds 100 in total.
+UPDATE user_asset_groupings
+SET weight = (
+    SELECT rtrim(rtrim(printf('%.2f', (10000 / COUNT(*)) / 100.0), '0'), '.')
+    FROM user_asset_groupings AS sibling
+    WHERE sibling.account_id = user_asset_groupings.account_id
+        AND sibling.asset_master_id = user_asset_groupings.asset_master_id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_asset_groupings_account_asset_category
+ON user_asset_groupings (account_id, asset_master_id, category_id);
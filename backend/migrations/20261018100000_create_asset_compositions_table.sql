@@ -0,0 +1,18 @@
+-- Add migration script here
+CREATE TABLE asset_compositions (
+    id TEXT PRIMARY KEY NOT NULL,
+    asset_master_id TEXT NOT NULL,
+    dimension TEXT NOT NULL,
+    label TEXT NOT NULL,
+    weight TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (asset_master_id) REFERENCES asset_master(id),
+    UNIQUE (asset_master_id, dimension, label)
+);
+
+CREATE TRIGGER IF NOT EXISTS update_asset_compositions_modtime
+AFTER UPDATE ON asset_compositions
+BEGIN
+    UPDATE asset_compositions SET updated_at = CURRENT_TIMESTAMP WHERE id = old.id;
+END;
@@ -0,0 +1,37 @@
+-- Add migration script here
+CREATE TABLE rebalance_plans (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'draft',
+    note TEXT,
+    applied_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE TRIGGER IF NOT EXISTS update_rebalance_plans_modtime
+AFTER UPDATE ON rebalance_plans
+BEGIN
+    UPDATE rebalance_plans SET updated_at = CURRENT_TIMESTAMP WHERE id = old.id;
+END;
+
+CREATE TABLE rebalance_plan_items (
+    id TEXT PRIMARY KEY NOT NULL,
+    plan_id TEXT NOT NULL,
+    asset_master_id TEXT NOT NULL,
+    planned_amount TEXT NOT NULL,
+    executed_amount TEXT,
+    executed_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (plan_id) REFERENCES rebalance_plans(id),
+    FOREIGN KEY (asset_master_id) REFERENCES asset_master(id)
+);
+
+CREATE TRIGGER IF NOT EXISTS update_rebalance_plan_items_modtime
+AFTER UPDATE ON rebalance_plan_items
+BEGIN
+    UPDATE rebalance_plan_items SET updated_at = CURRENT_TIMESTAMP WHERE id = old.id;
+END;
@@ -0,0 +1,11 @@
+-- Add migration script here
+ALTER TABLE accounts ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE asset_categories ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE user_asset_groupings ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE assets ADD COLUMN deleted_at TIMESTAMP;
+
+-- Deleted groupings must not block re-creating the same assignment
+DROP INDEX IF EXISTS idx_user_asset_groupings_account_asset_category;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_asset_groupings_account_asset_category
+ON user_asset_groupings (account_id, asset_master_id, category_id)
+WHERE deleted_at IS NULL;
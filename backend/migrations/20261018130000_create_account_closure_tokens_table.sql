@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE TABLE account_closure_tokens (
+    account_id TEXT PRIMARY KEY NOT NULL,
+    token TEXT NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE TRIGGER IF NOT EXISTS update_account_closure_tokens_modtime
+AFTER UPDATE ON account_closure_tokens
+BEGIN
+    UPDATE account_closure_tokens SET updated_at = CURRENT_TIMESTAMP WHERE account_id = old.account_id;
+END;
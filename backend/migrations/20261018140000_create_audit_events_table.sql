@@ -0,0 +1,26 @@
+-- Add migration script here
+CREATE TABLE audit_events (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT,
+    actor TEXT,
+    entity_type TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    before_json TEXT,
+    after_json TEXT,
+    occurred_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_account_occurred
+ON audit_events (account_id, occurred_at);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_entity
+ON audit_events (entity_type, entity_id);
+
+CREATE TRIGGER IF NOT EXISTS update_audit_events_modtime
+AFTER UPDATE ON audit_events
+BEGIN
+    UPDATE audit_events SET updated_at = CURRENT_TIMESTAMP WHERE id = old.id;
+END;
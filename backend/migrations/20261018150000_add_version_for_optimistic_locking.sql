@@ -0,0 +1,42 @@
+-- Add migration script here
+ALTER TABLE accounts ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE asset_master ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE asset_categories ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE user_asset_groupings ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE assets ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- The modtime triggers now also bump the version, so every UPDATE invalidates old ETags
+DROP TRIGGER IF EXISTS update_accounts_modtime;
+CREATE TRIGGER IF NOT EXISTS update_accounts_modtime
+AFTER UPDATE ON accounts
+BEGIN
+    UPDATE accounts SET updated_at = CURRENT_TIMESTAMP, version = old.version + 1 WHERE id = old.id;
+END;
+
+DROP TRIGGER IF EXISTS update_asset_master_modtime;
+CREATE TRIGGER IF NOT EXISTS update_asset_master_modtime
+AFTER UPDATE ON asset_master
+BEGIN
+    UPDATE asset_master SET updated_at = CURRENT_TIMESTAMP, version = old.version + 1 WHERE id = old.id;
+END;
+
+DROP TRIGGER IF EXISTS update_asset_categories_modtime;
+CREATE TRIGGER IF NOT EXISTS update_asset_categories_modtime
+AFTER UPDATE ON asset_categories
+BEGIN
+    UPDATE asset_categories SET updated_at = CURRENT_TIMESTAMP, version = old.version + 1 WHERE id = old.id;
+END;
+
+DROP TRIGGER IF EXISTS update_user_asset_groupings_modtime;
+CREATE TRIGGER IF NOT EXISTS update_user_asset_groupings_modtime
+AFTER UPDATE ON user_asset_groupings
+BEGIN
+    UPDATE user_asset_groupings SET updated_at = CURRENT_TIMESTAMP, version = old.version + 1 WHERE id = old.id;
+END;
+
+DROP TRIGGER IF EXISTS update_assets_modtime;
+CREATE TRIGGER IF NOT EXISTS update_assets_modtime
+AFTER UPDATE ON assets
+BEGIN
+    UPDATE assets SET updated_at = CURRENT_TIMESTAMP, version = old.version + 1 WHERE id = old.id;
+END;
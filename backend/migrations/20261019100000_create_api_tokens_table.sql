@@ -0,0 +1,23 @@
+-- Add migration script here
+CREATE TABLE api_tokens (
+    id TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    token_prefix TEXT NOT NULL,
+    scopes TEXT NOT NULL DEFAULT '',
+    expires_at TIMESTAMP,
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE INDEX idx_api_tokens_account_id ON api_tokens(account_id);
+
+CREATE TRIGGER IF NOT EXISTS update_api_tokens_modtime
+AFTER UPDATE ON api_tokens
+BEGIN
+    UPDATE api_tokens SET updated_at = CURRENT_TIMESTAMP WHERE id = old.id;
+END;
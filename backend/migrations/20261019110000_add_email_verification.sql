@@ -0,0 +1,19 @@
+-- Add migration script here
+ALTER TABLE accounts ADD COLUMN email_verified_at TIMESTAMP;
+ALTER TABLE accounts ADD COLUMN pending_email TEXT;
+
+CREATE TABLE email_verification_tokens (
+    account_id TEXT PRIMARY KEY NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    email TEXT NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (account_id) REFERENCES accounts(id)
+);
+
+CREATE TRIGGER IF NOT EXISTS update_email_verification_tokens_modtime
+AFTER UPDATE ON email_verification_tokens
+BEGIN
+    UPDATE email_verification_tokens SET updated_at = CURRENT_TIMESTAMP WHERE account_id = old.account_id;
+END;
@@ -0,0 +1,8 @@
+-- Add migration script here
+-- Names and emails become unique regardless of case, width and surrounding spaces.
+-- name_key is the NFKC-normalized, lowercased name. SQLite's lower() only folds ASCII, so
+-- existing accounts keep a NULL key here and the server fills it in Rust on startup,
+-- refusing to start when two accounts would collide. A unique index allows many NULLs.
+ALTER TABLE accounts ADD COLUMN name_key TEXT;
+
+CREATE UNIQUE INDEX idx_accounts_name_key ON accounts(name_key);
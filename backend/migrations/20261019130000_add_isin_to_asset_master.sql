@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE asset_master ADD COLUMN isin TEXT;
+
+CREATE UNIQUE INDEX idx_asset_master_isin ON asset_master(isin) WHERE isin IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE assets ADD COLUMN is_liability BOOLEAN NOT NULL DEFAULT 0;
+
+-- Negative amounts were the only way to record a loan; keep their meaning as explicit liabilities
+UPDATE assets
+SET is_liability = 1, current_amount = SUBSTR(current_amount, 2)
+WHERE current_amount LIKE '-%';
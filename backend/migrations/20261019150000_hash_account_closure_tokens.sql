@@ -0,0 +1,5 @@
+-- Add migration script here
+-- Tokens live for 15 minutes, so outstanding raw ones are dropped instead of rehashed
+DELETE FROM account_closure_tokens;
+
+ALTER TABLE account_closure_tokens RENAME COLUMN token TO token_hash;
@@ -0,0 +1,60 @@
+-- Add migration script here
+-- An account holds each asset at most once. Existing duplicates are merged into the oldest
+-- active holding, netting liabilities against positions, and the others move to trash.
+UPDATE assets
+SET current_amount = (
+        SELECT rtrim(rtrim(printf('%.4f', abs(sum(
+            CASE WHEN dup.is_liability THEN -CAST(dup.current_amount AS REAL)
+                 ELSE CAST(dup.current_amount AS REAL) END
+        ))), '0'), '.')
+        FROM assets AS dup
+        WHERE dup.account_id = assets.account_id
+            AND dup.asset_master_id = assets.asset_master_id
+            AND dup.deleted_at IS NULL
+    ),
+    is_liability = (
+        SELECT sum(
+            CASE WHEN dup.is_liability THEN -CAST(dup.current_amount AS REAL)
+                 ELSE CAST(dup.current_amount AS REAL) END
+        ) < 0
+        FROM assets AS dup
+        WHERE dup.account_id = assets.account_id
+            AND dup.asset_master_id = assets.asset_master_id
+            AND dup.deleted_at IS NULL
+    )
+WHERE deleted_at IS NULL
+    AND EXISTS (
+        SELECT 1 FROM assets AS dup
+        WHERE dup.account_id = assets.account_id
+            AND dup.asset_master_id = assets.asset_master_id
+            AND dup.deleted_at IS NULL
+            AND dup.id <> assets.id
+    )
+    AND NOT EXISTS (
+        SELECT 1 FROM assets AS older
+        WHERE older.account_id = assets.account_id
+            AND older.asset_master_id = assets.asset_master_id
+            AND older.deleted_at IS NULL
+            AND (
+                older.created_at < assets.created_at
+                OR (older.created_at = assets.created_at AND older.id < assets.id)
+            )
+    );
+
+UPDATE assets
+SET deleted_at = strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+WHERE deleted_at IS NULL
+    AND EXISTS (
+        SELECT 1 FROM assets AS older
+        WHERE older.account_id = assets.account_id
+            AND older.asset_master_id = assets.asset_master_id
+            AND older.deleted_at IS NULL
+            AND (
+                older.created_at < assets.created_at
+                OR (older.created_at = assets.created_at AND older.id < assets.id)
+            )
+    );
+
+CREATE UNIQUE INDEX idx_assets_account_asset_active
+ON assets (account_id, asset_master_id)
+WHERE deleted_at IS NULL;
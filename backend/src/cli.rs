@@ -0,0 +1,47 @@
+use crate::core::database::DbPool;
+use crate::core::error::{AppError, AppResult};
+use crate::domains::admin::model::BackupInfo;
+use crate::domains::admin::service::{BackupService, PurgeService};
+
+const USAGE: &str = "usage: backend <backup <create|list|restore <name>> | purge>";
+
+/// Runs an admin subcommand instead of the server.
+///
+/// `backup restore` overwrites live data and refuses to run unless every server on
+/// the database is stopped or in maintenance mode.
+pub async fn run(pool: &DbPool, args: &[String]) -> AppResult<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["backup", "create"] => {
+            let backup = BackupService::create(pool).await?;
+            println!("Created {}", describe(&backup));
+        }
+        ["backup", "list"] => {
+            for backup in BackupService::list().await? {
+                println!("{}", describe(&backup));
+            }
+        }
+        ["backup", "restore", name] => {
+            let backup = BackupService::restore(pool, name).await?;
+            println!("Restored {}", describe(&backup));
+        }
+        ["purge"] => {
+            let summary = PurgeService::run(pool).await?;
+            println!(
+                "Purged {} accounts, {} categories, {} groupings, {} holdings",
+                summary.accounts, summary.categories, summary.groupings, summary.holdings
+            );
+        }
+        _ => return Err(AppError::BadRequest(USAGE.into())),
+    }
+    Ok(())
+}
+
+fn describe(backup: &BackupInfo) -> String {
+    format!(
+        "{}\t{}\t{} bytes",
+        backup.name,
+        backup.created_at.to_rfc3339(),
+        backup.size_bytes
+    )
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs};
+
+use axum::http::HeaderValue;
+use serde::Deserialize;
+
+/// Read when `CONFIG_FILE` is unset; a missing default file is not an error
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+/// Rejects admin tokens short enough to guess
+const MIN_ADMIN_TOKEN_LEN: usize = 16;
+
+/// Server settings, loaded from defaults, then the TOML file, then environment variables
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub rate_limit: RateLimitConfig,
+    pub mail: MailConfig,
+    pub admin: AdminConfig,
+    pub accounts: AccountsConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Largest accepted request body
+    pub body_limit_bytes: usize,
+    /// Largest accepted `application/json` body; statement uploads use `body_limit_bytes`
+    pub json_body_limit_bytes: usize,
+    pub request_timeout_secs: u64,
+    /// Origins allowed to call the API from a browser; CORS is off when empty
+    pub cors_allowed_origins: Vec<String>,
+    pub swagger_ui: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 8000,
+            body_limit_bytes: 2 * 1024 * 1024,
+            json_body_limit_bytes: 256 * 1024,
+            request_timeout_secs: 30,
+            cors_allowed_origins: Vec::new(),
+            swagger_ui: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    /// Queries slower than this are logged as warnings under the `sqlx::query` target
+    pub slow_query_ms: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            max_connections: 5,
+            min_connections: 0,
+            acquire_timeout_secs: 30,
+            slow_query_ms: 200,
+        }
+    }
+}
+
+/// Token-bucket quotas; each bucket holds a minute's worth of requests
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub per_ip_per_minute: u32,
+    /// Applies per API-token account, or per `X-Account-ID` and IP for header-only requests,
+    /// on top of the IP quota
+    pub per_account_per_minute: u32,
+    /// Extra quota for expensive endpoints such as statement imports
+    pub expensive_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            per_ip_per_minute: 300,
+            per_account_per_minute: 120,
+            expensive_per_minute: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    /// Sender address of outbound mail
+    pub from: String,
+    /// Messages are saved here as `.eml` files instead of being logged
+    pub outbox_dir: Option<String>,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            from: "no-reply@localhost".to_string(),
+            outbox_dir: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// Secret expected in `X-Admin-Token`; the admin API is disabled when unset
+    pub token: Option<String>,
+    pub backup_dir: String,
+    /// Number of newest backups kept after each new one
+    pub backup_retention: usize,
+    /// Soft-deleted rows older than this are purged
+    pub soft_delete_retention_days: u32,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            token: None,
+            backup_dir: "backups".to_string(),
+            backup_retention: 7,
+            soft_delete_retention_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountsConfig {
+    /// Closing an account needs a token from the confirmation endpoint
+    pub closure_requires_confirmation: bool,
+}
+
+impl Default for AccountsConfig {
+    fn default() -> Self {
+        Self {
+            closure_requires_confirmation: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigError(String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            Err(_) => Self::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| ConfigError(format!("cannot read {}: {err}", path.display())))?;
+        toml::from_str(&text).map_err(|err| ConfigError(format!("{}: {err}", path.display())))
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        let server = &mut self.server;
+        override_from_env("HOST", &mut server.host)?;
+        override_from_env("PORT", &mut server.port)?;
+        override_from_env("BODY_LIMIT_BYTES", &mut server.body_limit_bytes)?;
+        override_from_env("JSON_BODY_LIMIT_BYTES", &mut server.json_body_limit_bytes)?;
+        override_from_env("REQUEST_TIMEOUT_SECS", &mut server.request_timeout_secs)?;
+        override_from_env("SWAGGER_UI", &mut server.swagger_ui)?;
+        if let Ok(origins) = env::var("CORS_ALLOWED_ORIGINS") {
+            server.cors_allowed_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        let database = &mut self.database;
+        override_from_env("DATABASE_URL", &mut database.url)?;
+        override_from_env("DB_MAX_CONNECTIONS", &mut database.max_connections)?;
+        override_from_env("DB_MIN_CONNECTIONS", &mut database.min_connections)?;
+        override_from_env(
+            "DB_ACQUIRE_TIMEOUT_SECS",
+            &mut database.acquire_timeout_secs,
+        )?;
+        override_from_env("SLOW_QUERY_MS", &mut database.slow_query_ms)?;
+
+        let rate_limit = &mut self.rate_limit;
+        override_from_env("RATE_LIMIT_ENABLED", &mut rate_limit.enabled)?;
+        override_from_env("RATE_LIMIT_PER_IP", &mut rate_limit.per_ip_per_minute)?;
+        override_from_env(
+            "RATE_LIMIT_PER_ACCOUNT",
+            &mut rate_limit.per_account_per_minute,
+        )?;
+        override_from_env("RATE_LIMIT_EXPENSIVE", &mut rate_limit.expensive_per_minute)?;
+
+        let mail = &mut self.mail;
+        override_from_env("MAIL_FROM", &mut mail.from)?;
+        if let Ok(dir) = env::var("MAIL_OUTBOX_DIR") {
+            mail.outbox_dir = Some(dir).filter(|dir| !dir.trim().is_empty());
+        }
+
+        let admin = &mut self.admin;
+        if let Ok(token) = env::var("ADMIN_TOKEN") {
+            admin.token = Some(token).filter(|token| !token.trim().is_empty());
+        }
+        override_from_env("BACKUP_DIR", &mut admin.backup_dir)?;
+        override_from_env("BACKUP_RETENTION", &mut admin.backup_retention)?;
+        override_from_env(
+            "SOFT_DELETE_RETENTION_DAYS",
+            &mut admin.soft_delete_retention_days,
+        )?;
+
+        override_from_env(
+            "ACCOUNT_CLOSURE_REQUIRE_CONFIRMATION",
+            &mut self.accounts.closure_requires_confirmation,
+        )?;
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.bind_address()?;
+        self.cors_origins()?;
+        if self.database.url.trim().is_empty() {
+            return Err(ConfigError(
+                "DATABASE_URL (or database.url) must be set".into(),
+            ));
+        }
+        if self.database.max_connections == 0 {
+            return Err(ConfigError(
+                "database.max_connections must be at least 1".into(),
+            ));
+        }
+        if self.database.min_connections > self.database.max_connections {
+            return Err(ConfigError(
+                "database.min_connections cannot exceed database.max_connections".into(),
+            ));
+        }
+        if self.server.request_timeout_secs == 0 {
+            return Err(ConfigError(
+                "server.request_timeout_secs must be at least 1".into(),
+            ));
+        }
+        if self
+            .admin
+            .token
+            .as_ref()
+            .is_some_and(|token| token.len() < MIN_ADMIN_TOKEN_LEN)
+        {
+            return Err(ConfigError(format!(
+                "admin.token must be at least {MIN_ADMIN_TOKEN_LEN} characters"
+            )));
+        }
+        if self.admin.backup_dir.trim().is_empty() {
+            return Err(ConfigError("admin.backup_dir cannot be empty".into()));
+        }
+        if self.admin.backup_retention == 0 {
+            return Err(ConfigError(
+                "admin.backup_retention must be at least 1".into(),
+            ));
+        }
+        let rate_limit = &self.rate_limit;
+        if rate_limit.enabled
+            && [
+                rate_limit.per_ip_per_minute,
+                rate_limit.per_account_per_minute,
+                rate_limit.expensive_per_minute,
+            ]
+            .contains(&0)
+        {
+            return Err(ConfigError(
+                "rate_limit quotas must be at least 1; set rate_limit.enabled = false to turn it off"
+                    .into(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn bind_address(&self) -> Result<SocketAddr, ConfigError> {
+        let address = format!("{}:{}", self.server.host, self.server.port);
+        address
+            .parse()
+            .map_err(|_| ConfigError(format!("{address} is not a valid bind address")))
+    }
+
+    pub fn cors_origins(&self) -> Result<Vec<HeaderValue>, ConfigError> {
+        self.server
+            .cors_allowed_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin)
+                    .map_err(|_| ConfigError(format!("{origin} is not a valid CORS origin")))
+            })
+            .collect()
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.server.request_timeout_secs)
+    }
+}
+
+fn override_from_env<T: FromStr>(name: &str, target: &mut T) -> Result<(), ConfigError> {
+    if let Ok(value) = env::var(name) {
+        *target = value
+            .trim()
+            .parse()
+            .map_err(|_| ConfigError(format!("{name}={value} cannot be parsed")))?;
+    }
+    Ok(())
+}
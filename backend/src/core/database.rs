@@ -1,15 +1,92 @@
-use sqlx::sqlite::SqlitePoolOptions;
-use sqlx::{Pool, Sqlite};
-use std::env;
+use log::LevelFilter;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{ConnectOptions, Pool, Sqlite, SqliteConnection};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::core::config::DatabaseConfig;
+use crate::core::error::AppResult;
 
 pub type DbPool = Pool<Sqlite>;
 
-pub async fn init_db() -> DbPool {
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+/// Connection handed to repositories. Both pooled connections and transactions deref to it.
+pub type DbConn = SqliteConnection;
+
+pub async fn init_db(config: &DatabaseConfig) -> Result<DbPool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(&config.url)?
+        .log_statements(LevelFilter::Debug)
+        .log_slow_statements(
+            LevelFilter::Warn,
+            Duration::from_millis(config.slow_query_ms),
+        );
 
     SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(&database_url)
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+        .connect_with(options)
+        .await
+}
+
+/// Lock file beside the database file that keeps restores and live servers apart
+pub fn lock_path(config: &DatabaseConfig) -> Result<PathBuf, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(&config.url)?;
+    Ok(PathBuf::from(format!(
+        "{}.lock",
+        options.get_filename().display()
+    )))
+}
+
+/// Folds the WAL back into the database file and closes every pooled connection
+pub async fn close_db(pool: &DbPool) {
+    if let Err(err) = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await
+    {
+        tracing::warn!(error = %err, "WAL checkpoint failed");
+    }
+    pool.close().await;
+}
+
+/// Unit of work: runs `work` inside a transaction, committing on success and
+/// rolling back on any error.
+///
+/// The write lock is taken up front, so concurrent units queue on the busy timeout
+/// instead of failing when one of them upgrades a stale read snapshot to a write.
+pub async fn transaction<T>(
+    pool: &DbPool,
+    work: impl AsyncFnOnce(&mut DbConn) -> AppResult<T>,
+) -> AppResult<T> {
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let value = work(&mut tx).await?;
+    tx.commit().await?;
+    Ok(value)
+}
+
+/// Fresh in-memory database with every migration applied
+#[cfg(test)]
+pub async fn test_pool() -> DbPool {
+    // One connection that never closes, since each in-memory connection is its own database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
         .await
-        .expect("Failed to connect to database")
+        .expect("in-memory database opens");
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/migrations");
+    let mut migrations: Vec<PathBuf> = std::fs::read_dir(dir)
+        .expect("migrations directory is readable")
+        .map(|entry| entry.expect("migration entry is readable").path())
+        .collect();
+    migrations.sort();
+    for path in migrations {
+        let sql = std::fs::read_to_string(&path).expect("migration is readable");
+        sqlx::raw_sql(&sql)
+            .execute(&pool)
+            .await
+            .unwrap_or_else(|err| panic!("{} applies: {err}", path.display()));
+    }
+    pool
 }
@@ -1,27 +1,120 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{
+        HeaderValue, StatusCode,
+        header::{CONTENT_TYPE, RETRY_AFTER},
+    },
     response::{IntoResponse, Response},
 };
-use serde_json::json;
+use serde::Serialize;
+use utoipa::openapi::{
+    Content, OpenApi, Ref, RefOr, ResponseBuilder,
+    header::HeaderBuilder,
+    schema::{ObjectBuilder, Type},
+};
 
 pub type AppResult<T> = Result<T, AppError>;
 
-use validator::ValidationErrors;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use crate::middleware::request_id::current_request_id;
 
 #[derive(Debug)]
 pub enum AppError {
     Database(sqlx::Error),
+    Io(std::io::Error),
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
     NotFound(String),
+    Conflict(String),
+    /// `If-Match` named an outdated version; carries the entity as it is now
+    PreconditionFailed(serde_json::Value),
     Validation(ValidationErrors),
+    /// Well-formed request that refers to data it may not use, e.g. another account's row
+    Unprocessable(ValidationErrors),
+    PayloadTooLarge(String),
+    /// Seconds until the client may retry
+    RateLimited(u64),
+    ServiceUnavailable(String),
+}
+
+/// Stable, machine-readable identifier of an error response
+#[derive(Debug, Clone, Copy, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InternalError,
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    PreconditionFailed,
+    ValidationFailed,
+    UnprocessableEntity,
+    PayloadTooLarge,
+    RateLimited,
+    ServiceUnavailable,
+}
+
+/// Error body in the RFC 7807 `application/problem+json` format
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ProblemDetails {
+    /// Always `about:blank`; `code` identifies the problem
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub code: ErrorCode,
+    pub detail: String,
+    /// ID of the request, also sent as the X-Request-ID header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Field-level problems of validation errors
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    /// Current state of the entity when a precondition failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub current: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct FieldError {
+    /// Path of the offending field, e.g. `splits[1].weight`; absent for whole-request rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
+        // The raw messages name tables and columns, so they are logged rather than returned
+        if let Some(db_err) = err.as_database_error() {
+            if db_err.is_unique_violation() {
+                tracing::warn!(error = db_err.message(), "unique constraint violated");
+                return AppError::Conflict("The resource already exists".into());
+            }
+            // SQLite does not say which side of the reference is missing or still in use
+            if db_err.is_foreign_key_violation() {
+                tracing::warn!(error = db_err.message(), "foreign key constraint violated");
+                return AppError::Conflict(
+                    "The change refers to a missing resource or one that is still in use".into(),
+                );
+            }
+        }
         AppError::Database(err)
     }
 }
 
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        AppError::Io(err)
+    }
+}
+
 impl From<ValidationErrors> for AppError {
     fn from(err: ValidationErrors) -> Self {
         AppError::Validation(err)
@@ -30,21 +123,224 @@ impl From<ValidationErrors> for AppError {
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
+        let mut retry_after = None;
+        let mut errors = Vec::new();
+        let mut current = None;
+        let (status, code, detail) = match self {
             AppError::Database(err) => {
-                eprintln!("Database error: {:?}", err);
+                tracing::error!(error = ?err, "database error");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    json!({"error": "Internal Server Error"}),
+                    ErrorCode::InternalError,
+                    "Internal Server Error".to_string(),
                 )
             }
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, json!({"error": msg})),
-            AppError::Validation(err) => (
-                StatusCode::BAD_REQUEST,
-                json!({"error": "Validation error", "details": err}),
+            AppError::Io(err) => {
+                tracing::error!(error = ?err, "I/O error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ErrorCode::InternalError,
+                    "Internal Server Error".to_string(),
+                )
+            }
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, ErrorCode::BadRequest, msg),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, ErrorCode::Forbidden, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, ErrorCode::NotFound, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, ErrorCode::Conflict, msg),
+            AppError::PreconditionFailed(state) => {
+                current = Some(state);
+                (
+                    StatusCode::PRECONDITION_FAILED,
+                    ErrorCode::PreconditionFailed,
+                    "Resource has been modified".to_string(),
+                )
+            }
+            AppError::Validation(err) => {
+                collect_field_errors(&err, None, &mut errors);
+                (
+                    StatusCode::BAD_REQUEST,
+                    ErrorCode::ValidationFailed,
+                    "Validation error".to_string(),
+                )
+            }
+            AppError::Unprocessable(err) => {
+                collect_field_errors(&err, None, &mut errors);
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ErrorCode::UnprocessableEntity,
+                    "Request refers to data that cannot be used".to_string(),
+                )
+            }
+            AppError::PayloadTooLarge(msg) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ErrorCode::PayloadTooLarge,
+                msg,
             ),
+            AppError::RateLimited(seconds) => {
+                retry_after = Some(seconds);
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    ErrorCode::RateLimited,
+                    format!("Too many requests, retry in {seconds} seconds"),
+                )
+            }
+            AppError::ServiceUnavailable(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::ServiceUnavailable,
+                msg,
+            ),
+        };
+        errors.sort_by(|a, b| a.field.cmp(&b.field));
+
+        let problem = ProblemDetails {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            code,
+            detail,
+            request_id: current_request_id(),
+            errors,
+            current,
+        };
+
+        let mut response = (status, Json(problem)).into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        if let Some(seconds) = retry_after {
+            response.headers_mut().insert(RETRY_AFTER, seconds.into());
+        }
+        response
+    }
+}
+
+/// Flattens nested validator errors into `field.path[index]` entries
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    prefix: Option<&str>,
+    out: &mut Vec<FieldError>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = match (prefix, field.as_ref()) {
+            (prefix, "__all__") => prefix.map(str::to_string),
+            (Some(prefix), field) => Some(format!("{prefix}.{field}")),
+            (None, field) => Some(field.to_string()),
+        };
+        match kind {
+            ValidationErrorsKind::Field(items) => {
+                out.extend(items.iter().map(|item| FieldError {
+                    field: path.clone(),
+                    code: item.code.to_string(),
+                    message: item.message.as_ref().map(|msg| msg.to_string()),
+                }));
+            }
+            ValidationErrorsKind::Struct(nested) => {
+                collect_field_errors(nested, path.as_deref(), out);
+            }
+            ValidationErrorsKind::List(items) => {
+                let path = path.unwrap_or_default();
+                for (index, nested) in items {
+                    collect_field_errors(nested, Some(&format!("{path}[{index}]")), out);
+                }
+            }
+        }
+    }
+}
+
+/// Shared response of every rate-limited route, i.e. everything under `/api/`
+const RATE_LIMITED_RESPONSE: &str = "RateLimited";
+
+/// Documents every error response of the API as a `ProblemDetails` body, and the 429 of
+/// the rate-limited routes
+pub struct ProblemResponses;
+
+impl utoipa::Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let rate_limited = ResponseBuilder::new()
+            .description("Rate limit exceeded")
+            .header(
+                "Retry-After",
+                HeaderBuilder::new()
+                    .schema(ObjectBuilder::new().schema_type(Type::Integer))
+                    .description(Some("Seconds until the request may be retried"))
+                    .build(),
+            )
+            .content(
+                "application/problem+json",
+                Content::new(Some(Ref::from_schema_name("ProblemDetails"))),
+            )
+            .build();
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .responses
+            .insert(RATE_LIMITED_RESPONSE.to_string(), RefOr::T(rate_limited));
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            let rate_limited = path.starts_with("/api/");
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.patch,
+            ];
+            for operation in operations.into_iter().flatten() {
+                if rate_limited {
+                    operation
+                        .responses
+                        .responses
+                        .entry("429".to_string())
+                        .or_insert_with(|| Ref::from_response_name(RATE_LIMITED_RESPONSE).into());
+                }
+                for (status, response) in operation.responses.responses.iter_mut() {
+                    let RefOr::T(response) = response else {
+                        continue;
+                    };
+                    if status.starts_with(['4', '5']) && response.content.is_empty() {
+                        response.content.insert(
+                            "application/problem+json".to_string(),
+                            Content::new(Some(Ref::from_schema_name("ProblemDetails"))),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AppError;
+    use crate::core::database::test_pool;
+
+    #[tokio::test]
+    async fn constraint_failures_are_conflicts_without_schema_details() {
+        let pool = test_pool().await;
+        let insert = "INSERT INTO asset_master (id, name) VALUES (?, 'Fund')";
+        sqlx::query(insert)
+            .bind("first")
+            .execute(&pool)
+            .await
+            .expect("catalog asset is created");
+
+        let duplicate = sqlx::query(insert).bind("second").execute(&pool).await;
+        let Err(AppError::Conflict(detail)) = duplicate.map_err(AppError::from) else {
+            panic!("expected a conflict for the duplicate name");
         };
+        assert!(!detail.contains("asset_master"), "{detail}");
 
-        (status, Json(message)).into_response()
+        let orphan = sqlx::query(
+            "INSERT INTO asset_compositions (id, asset_master_id, dimension, label, weight) \
+             VALUES ('c', 'missing', 'region', 'Japan', 100)",
+        )
+        .execute(&pool)
+        .await;
+        assert!(matches!(
+            orphan.map_err(AppError::from),
+            Err(AppError::Conflict(_))
+        ));
     }
 }
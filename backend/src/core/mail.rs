@@ -0,0 +1,121 @@
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::OnceLock;
+
+use chrono::Utc;
+
+use crate::core::config::MailConfig;
+use crate::shared::util::generate_id;
+
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'a>>;
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    /// Part of the body, such as a one-time token, that must never reach the log
+    pub secret: Option<String>,
+}
+
+impl Mail {
+    /// The body with the secret replaced, safe to log
+    pub fn redacted_body(&self) -> String {
+        match self.secret.as_deref() {
+            Some(secret) if !secret.is_empty() => self.body.replace(secret, "[redacted]"),
+            _ => self.body.clone(),
+        }
+    }
+}
+
+/// Delivers outbound mail; swap the implementation to change transports
+pub trait MailSender: Send + Sync {
+    fn send<'a>(&'a self, mail: &'a Mail) -> SendFuture<'a>;
+}
+
+/// Writes each message to the log instead of delivering it, with the secret redacted;
+/// use the outbox directory to read the full message during development
+pub struct LogMailer;
+
+impl MailSender for LogMailer {
+    fn send<'a>(&'a self, mail: &'a Mail) -> SendFuture<'a> {
+        Box::pin(async move {
+            tracing::info!(
+                from = %mail.from,
+                to = %mail.to,
+                subject = %mail.subject,
+                body = %mail.redacted_body(),
+                "mail not delivered; logged instead"
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Saves each message as an `.eml` file in a directory, for development and tests
+pub struct FileMailer {
+    pub dir: PathBuf,
+}
+
+impl MailSender for FileMailer {
+    fn send<'a>(&'a self, mail: &'a Mail) -> SendFuture<'a> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            let now = Utc::now();
+            let path = self.dir.join(format!(
+                "{}-{}.eml",
+                now.format("%Y%m%dT%H%M%S"),
+                generate_id()
+            ));
+            let message = format!(
+                "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+                mail.from,
+                mail.to,
+                mail.subject,
+                now.to_rfc2822(),
+                mail.body
+            );
+            tokio::fs::write(&path, message).await?;
+            tracing::info!(to = %mail.to, path = %path.display(), "mail written to outbox");
+            Ok(())
+        })
+    }
+}
+
+struct Mailer {
+    from: String,
+    sender: Box<dyn MailSender>,
+}
+
+static MAILER: OnceLock<Mailer> = OnceLock::new();
+
+/// Picks the sender from the configuration: the outbox directory when set, the log otherwise
+pub fn install(config: &MailConfig) {
+    let sender: Box<dyn MailSender> = match &config.outbox_dir {
+        Some(dir) => Box::new(FileMailer { dir: dir.into() }),
+        None => Box::new(LogMailer),
+    };
+    let _ = MAILER.set(Mailer {
+        from: config.from.clone(),
+        sender,
+    });
+}
+
+/// Sends through the installed sender, or the log when none was installed
+pub async fn send(to: &str, subject: &str, body: String, secret: Option<&str>) -> io::Result<()> {
+    let mailer = MAILER.get_or_init(|| Mailer {
+        from: MailConfig::default().from,
+        sender: Box::new(LogMailer),
+    });
+    let mail = Mail {
+        from: mailer.from.clone(),
+        to: to.to_string(),
+        subject: subject.to_string(),
+        body,
+        secret: secret.map(str::to_string),
+    };
+    mailer.sender.send(&mail).await
+}
@@ -0,0 +1,83 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::core::error::{AppError, AppResult};
+
+/// While set, only admin endpoints are served
+static MAINTENANCE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Lock file next to the database, shared by the server and CLI processes
+static LOCK_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Open lock file of a server in this process. The server holds the lock exclusively while
+/// it serves requests and releases it in maintenance mode, which is when a restore may take it.
+static SERVER_LOCK: Mutex<Option<File>> = Mutex::new(None);
+
+pub fn init(lock_path: PathBuf) {
+    let _ = LOCK_PATH.set(lock_path);
+}
+
+pub fn is_enabled() -> bool {
+    MAINTENANCE_MODE.load(Ordering::SeqCst)
+}
+
+/// Leaving maintenance mode fails while a restore, possibly from another process, is running
+pub fn set_enabled(enabled: bool) -> AppResult<()> {
+    let server_lock = SERVER_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(file) = server_lock.as_ref() {
+        if enabled {
+            file.unlock()?;
+        } else {
+            try_lock(
+                file,
+                "A restore is in progress; try again when it has finished",
+            )?;
+        }
+    }
+    MAINTENANCE_MODE.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Takes the lock for a server about to serve requests
+pub fn hold_server_lock() -> AppResult<()> {
+    let file = open_lock_file()?;
+    try_lock(
+        &file,
+        "The database is in use by another server or a running restore",
+    )?;
+    *SERVER_LOCK.lock().unwrap_or_else(|err| err.into_inner()) = Some(file);
+    Ok(())
+}
+
+/// Lock for the duration of a restore; released when the returned file is dropped.
+/// Fails while any server using the database is outside maintenance mode.
+pub fn lock_for_restore() -> AppResult<File> {
+    let file = open_lock_file()?;
+    try_lock(
+        &file,
+        "Restore requires maintenance mode; stop the server or enable maintenance mode first",
+    )?;
+    Ok(file)
+}
+
+fn open_lock_file() -> AppResult<File> {
+    let path = LOCK_PATH
+        .get()
+        .ok_or_else(|| AppError::Conflict("Maintenance lock is not initialized".into()))?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    Ok(file)
+}
+
+fn try_lock(file: &File, held_message: &str) -> AppResult<()> {
+    match file.try_lock() {
+        Ok(()) => Ok(()),
+        Err(TryLockError::WouldBlock) => Err(AppError::Conflict(held_message.into())),
+        Err(TryLockError::Error(err)) => Err(err.into()),
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::OnceLock;
+
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Latency buckets in seconds, shared by the request, query and simulation histograms
+const DURATION_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0,
+];
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Installs the global Prometheus recorder; metrics recorded before this are dropped
+pub fn install() {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".into()), &DURATION_BUCKETS)
+        .and_then(|builder| builder.install_recorder());
+    match handle {
+        Ok(handle) => {
+            let _ = HANDLE.set(handle);
+        }
+        Err(err) => tracing::error!(error = %err, "cannot install the metrics recorder"),
+    }
+}
+
+/// Current metrics in the Prometheus text format
+pub fn render() -> String {
+    HANDLE
+        .get()
+        .map(|handle| {
+            handle.run_upkeep();
+            handle.render()
+        })
+        .unwrap_or_default()
+}
+
+/// Turns the `sqlx::query` events sqlx emits after each statement into the
+/// `db_query_duration_seconds` histogram, labelled by statement kind
+pub struct QueryMetricsLayer;
+
+impl<S: Subscriber> Layer<S> for QueryMetricsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = QueryVisitor::default();
+        event.record(&mut visitor);
+        if let Some(elapsed) = visitor.elapsed_secs {
+            metrics::histogram!("db_query_duration_seconds", "statement" => visitor.statement)
+                .record(elapsed);
+        }
+    }
+}
+
+#[derive(Default)]
+struct QueryVisitor {
+    elapsed_secs: Option<f64>,
+    statement: &'static str,
+}
+
+impl Visit for QueryVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "summary" {
+            self.statement = statement_kind(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+fn statement_kind(summary: &str) -> &'static str {
+    let keyword = summary.split_whitespace().next().unwrap_or_default();
+    [
+        "SELECT", "INSERT", "UPDATE", "DELETE", "PRAGMA", "BEGIN", "COMMIT", "ROLLBACK",
+    ]
+    .into_iter()
+    .find(|kind| keyword.eq_ignore_ascii_case(kind))
+    .unwrap_or("OTHER")
+}
@@ -1,2 +1,8 @@
+pub mod config;
 pub mod database;
 pub mod error;
+pub mod mail;
+pub mod maintenance;
+pub mod metrics;
+pub mod shutdown;
+pub mod telemetry;
@@ -0,0 +1,42 @@
+use std::env;
+
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, fmt};
+
+use crate::core::metrics::QueryMetricsLayer;
+
+/// Used when `LOG_LEVEL` is unset or invalid
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Installs the global log subscriber.
+///
+/// `LOG_LEVEL` takes filter directives such as `debug` or `info,sqlx::query=debug`,
+/// and `LOG_FORMAT=json` switches from human-readable lines to one JSON object per event.
+/// Query timings always reach the metrics layer, whatever the log level.
+pub fn init_tracing() {
+    let filter =
+        EnvFilter::try_from_env("LOG_LEVEL").unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let json = env::var("LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+
+    let log_layer = if json {
+        fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_filter(filter)
+            .boxed()
+    } else {
+        fmt::layer().with_filter(filter).boxed()
+    };
+    let query_layer =
+        QueryMetricsLayer.with_filter(Targets::new().with_target("sqlx::query", Level::DEBUG));
+
+    tracing_subscriber::registry()
+        .with(log_layer)
+        .with(query_layer)
+        .init();
+}
@@ -1,7 +1,7 @@
 use axum::{
     Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 
@@ -9,23 +9,33 @@ use validator::Validate;
 
 use crate::core::database::DbPool;
 use crate::core::error::{AppError, AppResult};
-use crate::domains::accounts::model::Account;
-use crate::domains::accounts::schema::{CreateAccountRequest, UpdateAccountRequest};
-use crate::domains::accounts::service::AccountService;
+use crate::domains::accounts::model::{
+    Account, AccountClosureSummary, AccountExport, AccountImportSummary, ApiToken,
+    ClosureConfirmation, CreatedApiToken,
+};
+use crate::domains::accounts::schema::{
+    CloseAccountRequest, ConfirmEmailRequest, CreateAccountRequest, CreateApiTokenRequest,
+    PatchAccountRequest, UpdateAccountRequest,
+};
+use crate::domains::accounts::service::{AccountService, ApiTokenService};
+use crate::middleware::auth::token_account;
+use crate::middleware::extractor::{JsonBody, ensure_account};
+use crate::shared::etag::{etag, parse_if_match};
 
 #[utoipa::path(
     post,
     path = "/api/v1/accounts",
     request_body = CreateAccountRequest,
     responses(
-        (status = 201, description = "Account created successfully", body = Account),
-        (status = 400, description = "Bad request")
+        (status = 201, description = "Account created successfully; a verification mail is sent", body = Account),
+        (status = 400, description = "Bad request"),
+        (status = 409, description = "Another account uses the name or email")
     ),
     tag = "accounts"
 )]
 pub async fn create_account(
     State(pool): State<DbPool>,
-    Json(payload): Json<CreateAccountRequest>,
+    JsonBody(payload): JsonBody<CreateAccountRequest>,
 ) -> AppResult<impl IntoResponse> {
     payload.validate()?;
     let account = AccountService::create(&pool, payload.name, payload.email).await?;
@@ -52,7 +62,7 @@ pub async fn list_accounts(State(pool): State<DbPool>) -> AppResult<impl IntoRes
         ("id" = String, Path, description = "Account ID")
     ),
     responses(
-        (status = 200, description = "Account found", body = Account),
+        (status = 200, description = "Account found", body = Account, headers(("ETag" = String, description = "Current version of the account"))),
         (status = 404, description = "Account not found")
     ),
     tag = "accounts"
@@ -64,30 +74,86 @@ pub async fn get_account(
     let account = AccountService::get_by_id(&pool, &id)
         .await?
         .ok_or(AppError::NotFound("Account not found".into()))?;
-    Ok(Json(account))
+    Ok((etag(account.version), Json(account)))
 }
 
 #[utoipa::path(
     put,
     path = "/api/v1/accounts/{id}",
     params(
-        ("id" = String, Path, description = "Account ID")
+        ("id" = String, Path, description = "Account ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
     ),
     request_body = UpdateAccountRequest,
     responses(
-        (status = 200, description = "Account updated successfully", body = Account),
-        (status = 404, description = "Account not found")
+        (status = 200, description = "Account updated successfully; a new email stays pending until confirmed", body = Account, headers(("ETag" = String, description = "Current version of the account"))),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account, or changed the email without an API token"),
+        (status = 404, description = "Account not found"),
+        (status = 409, description = "Another account uses the name or email"),
+        (status = 412, description = "The account changed since the If-Match version; the body holds its current state"),
     ),
     tag = "accounts"
 )]
 pub async fn update_account(
     State(pool): State<DbPool>,
+    headers: HeaderMap,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateAccountRequest>,
+    JsonBody(payload): JsonBody<UpdateAccountRequest>,
 ) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    let expected_version = parse_if_match(&headers)?;
     payload.validate()?;
-    let account = AccountService::update(&pool, &id, payload.name, payload.email).await?;
-    Ok(Json(account))
+    let account = AccountService::update(
+        &pool,
+        &id,
+        Some(payload.name),
+        Some(payload.email),
+        token_account().is_some(),
+        expected_version,
+    )
+    .await?;
+    Ok((etag(account.version), Json(account)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/accounts/{id}",
+    params(
+        ("id" = String, Path, description = "Account ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
+    ),
+    request_body = PatchAccountRequest,
+    responses(
+        (status = 200, description = "Present fields of the account updated; a new email stays pending until confirmed", body = Account, headers(("ETag" = String, description = "Current version of the account"))),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account, or changed the email without an API token"),
+        (status = 404, description = "Account not found"),
+        (status = 409, description = "Another account uses the name or email"),
+        (status = 412, description = "The account changed since the If-Match version; the body holds its current state"),
+    ),
+    tag = "accounts"
+)]
+pub async fn patch_account(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<PatchAccountRequest>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    let expected_version = parse_if_match(&headers)?;
+    payload.validate()?;
+    let account = AccountService::update(
+        &pool,
+        &id,
+        payload.name,
+        payload.email,
+        token_account().is_some(),
+        expected_version,
+    )
+    .await?;
+    Ok((etag(account.version), Json(account)))
 }
 
 #[utoipa::path(
@@ -97,15 +163,361 @@ pub async fn update_account(
         ("id" = String, Path, description = "Account ID")
     ),
     responses(
-        (status = 204, description = "Account deleted successfully"),
+        (status = 204, description = "Account and its portfolio moved to trash"),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account"),
         (status = 404, description = "Account not found")
     ),
     tag = "accounts"
 )]
 pub async fn delete_account(
     State(pool): State<DbPool>,
+    headers: HeaderMap,
     Path(id): Path<String>,
 ) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
     AccountService::delete(&pool, &id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Account ID")
+    ),
+    responses(
+        (status = 200, description = "Account and the portfolio deleted with it restored", body = Account),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account"),
+        (status = 404, description = "No deleted account with this ID")
+    ),
+    tag = "accounts"
+)]
+pub async fn restore_account(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    let account = AccountService::restore(&pool, &id).await?;
+    Ok(Json(account))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/email/verification",
+    params(
+        ("id" = String, Path, description = "Account ID")
+    ),
+    responses(
+        (status = 202, description = "Verification mail sent to the pending address, or to the current one while unverified"),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account"),
+        (status = 404, description = "Account not found"),
+        (status = 409, description = "Email already verified and no change pending")
+    ),
+    tag = "accounts"
+)]
+pub async fn request_email_verification(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    AccountService::request_email_verification(&pool, &id).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/email/confirmation",
+    request_body = ConfirmEmailRequest,
+    responses(
+        (status = 200, description = "Address verified; a pending change is now the account's email", body = Account, headers(("ETag" = String, description = "Current version of the account"))),
+        (status = 400, description = "Token unknown, expired or already used"),
+        (status = 409, description = "Another account took the address in the meantime")
+    ),
+    tag = "accounts"
+)]
+pub async fn confirm_email(
+    State(pool): State<DbPool>,
+    JsonBody(payload): JsonBody<ConfirmEmailRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account = AccountService::confirm_email(&pool, &payload.token).await?;
+    Ok((etag(account.version), Json(account)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/closure/confirmation",
+    params(
+        ("id" = String, Path, description = "Account ID")
+    ),
+    responses(
+        (status = 201, description = "Confirmation token and the rows a closure would erase", body = ClosureConfirmation),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account"),
+        (status = 404, description = "Account not found")
+    ),
+    tag = "accounts"
+)]
+pub async fn request_account_closure(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    let confirmation = AccountService::request_closure(&pool, &id).await?;
+    Ok((StatusCode::CREATED, Json(confirmation)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/closure",
+    params(
+        ("id" = String, Path, description = "Account ID")
+    ),
+    request_body = CloseAccountRequest,
+    responses(
+        (status = 200, description = "Account and all of its data permanently erased", body = AccountClosureSummary),
+        (status = 400, description = "Missing, wrong or expired confirmation token"),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account"),
+        (status = 404, description = "Account not found")
+    ),
+    tag = "accounts"
+)]
+pub async fn close_account(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<CloseAccountRequest>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    let summary = AccountService::close(&pool, &id, payload.confirmation_token).await?;
+    Ok(Json(summary))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/{id}/export",
+    params(
+        ("id" = String, Path, description = "Account ID")
+    ),
+    responses(
+        (status = 200, description = "Versioned backup of the account and its portfolio", body = AccountExport),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account"),
+        (status = 404, description = "Account not found")
+    ),
+    tag = "accounts"
+)]
+pub async fn export_account(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    let document = AccountService::export(&pool, &id).await?;
+    Ok(Json(document))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/import",
+    request_body = AccountExport,
+    responses(
+        (status = 201, description = "Account restored under new IDs", body = AccountImportSummary),
+        (status = 400, description = "Bad request"),
+        (status = 422, description = "The document holds values the API would refuse; `errors` names each path")
+    ),
+    tag = "accounts"
+)]
+pub async fn import_account(
+    State(pool): State<DbPool>,
+    JsonBody(payload): JsonBody<AccountExport>,
+) -> AppResult<impl IntoResponse> {
+    let summary = AccountService::import(&pool, payload).await?;
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts/{id}/tokens",
+    params(
+        ("id" = String, Path, description = "Account ID")
+    ),
+    request_body = CreateApiTokenRequest,
+    responses(
+        (status = 201, description = "Token issued; the secret is shown only in this response", body = CreatedApiToken),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account or a scoped token"),
+        (status = 404, description = "Account not found")
+    ),
+    tag = "accounts"
+)]
+pub async fn create_api_token(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<CreateApiTokenRequest>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    payload.validate()?;
+    let created =
+        ApiTokenService::create(&pool, &id, payload.name, payload.scopes, payload.expires_at)
+            .await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts/{id}/tokens",
+    params(
+        ("id" = String, Path, description = "Account ID")
+    ),
+    responses(
+        (status = 200, description = "Tokens of the account, without their secrets", body = [ApiToken]),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account or a scoped token")
+    ),
+    tag = "accounts"
+)]
+pub async fn list_api_tokens(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    let tokens = ApiTokenService::get_all(&pool, &id).await?;
+    Ok(Json(tokens))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accounts/{id}/tokens/{token_id}",
+    params(
+        ("id" = String, Path, description = "Account ID"),
+        ("token_id" = String, Path, description = "API token ID")
+    ),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 401, description = "Caller not identified"),
+        (status = 403, description = "Caller is another account or a scoped token"),
+        (status = 404, description = "Token not found"),
+        (status = 409, description = "Token already revoked")
+    ),
+    tag = "accounts"
+)]
+pub async fn revoke_api_token(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path((id, token_id)): Path<(String, String)>,
+) -> AppResult<impl IntoResponse> {
+    ensure_account(&headers, &id)?;
+    ApiTokenService::revoke(&pool, &id, &token_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+    use axum::http::{Request, StatusCode};
+    use axum::middleware::from_fn_with_state;
+    use tower::ServiceExt;
+
+    use crate::core::database::test_pool;
+    use crate::domains::accounts::account_routes;
+    use crate::domains::accounts::service::{AccountService, ApiTokenService};
+    use crate::middleware::auth::authenticate;
+
+    #[tokio::test]
+    async fn another_accounts_token_cannot_change_the_account() {
+        let pool = test_pool().await;
+        let owner = AccountService::create(&pool, "owner".into(), "owner@example.com".into())
+            .await
+            .expect("owner is created");
+        let other = AccountService::create(&pool, "other".into(), "other@example.com".into())
+            .await
+            .expect("other account is created");
+        let token = ApiTokenService::create(&pool, &other.id, "cron".into(), Vec::new(), None)
+            .await
+            .expect("token is issued")
+            .token;
+        let app =
+            account_routes(pool.clone()).layer(from_fn_with_state(pool.clone(), authenticate));
+
+        let requests = [
+            ("PATCH", format!("/{}", owner.id), r#"{"name":"taken"}"#),
+            (
+                "PUT",
+                format!("/{}", owner.id),
+                r#"{"name":"taken","email":"attacker@example.com"}"#,
+            ),
+            ("DELETE", format!("/{}", owner.id), ""),
+            ("POST", format!("/{}/restore", owner.id), ""),
+        ];
+        for (method, uri, body) in requests {
+            let request = Request::builder()
+                .method(method)
+                .uri(&uri)
+                .header(AUTHORIZATION, format!("Bearer {token}"))
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .expect("request is valid");
+            let response = app.clone().oneshot(request).await.expect("router responds");
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{method} {uri}");
+        }
+
+        let account = AccountService::get_by_id(&pool, &owner.id)
+            .await
+            .expect("account is readable")
+            .expect("account still exists");
+        assert_eq!(account.name, "owner");
+        assert_eq!(account.pending_email, None);
+    }
+
+    #[tokio::test]
+    async fn email_change_needs_the_accounts_token() {
+        let pool = test_pool().await;
+        let owner = AccountService::create(&pool, "owner".into(), "owner@example.com".into())
+            .await
+            .expect("owner is created");
+        let token = ApiTokenService::create(&pool, &owner.id, "ui".into(), Vec::new(), None)
+            .await
+            .expect("token is issued")
+            .token;
+        let app =
+            account_routes(pool.clone()).layer(from_fn_with_state(pool.clone(), authenticate));
+        let change = |credential: (&'static str, String)| {
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/{}", owner.id))
+                .header(credential.0, credential.1)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"email":"new@example.com"}"#))
+                .expect("request is valid")
+        };
+
+        let by_header = app
+            .clone()
+            .oneshot(change(("X-Account-ID", owner.id.clone())))
+            .await
+            .expect("router responds");
+        assert_eq!(by_header.status(), StatusCode::FORBIDDEN);
+
+        let by_token = app
+            .oneshot(change((AUTHORIZATION.as_str(), format!("Bearer {token}"))))
+            .await
+            .expect("router responds");
+        assert_eq!(by_token.status(), StatusCode::OK);
+        let account = AccountService::get_by_id(&pool, &owner.id)
+            .await
+            .expect("account is readable")
+            .expect("account exists");
+        assert_eq!(account.pending_email.as_deref(), Some("new@example.com"));
+    }
+}
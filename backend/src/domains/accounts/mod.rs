@@ -8,7 +8,7 @@ use crate::core::database::DbPool;
 
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 
 pub fn account_routes(pool: DbPool) -> Router {
@@ -21,7 +21,26 @@ pub fn account_routes(pool: DbPool) -> Router {
             "/{id}",
             get(handler::get_account)
                 .put(handler::update_account)
+                .patch(handler::patch_account)
                 .delete(handler::delete_account),
         )
+        .route("/{id}/restore", post(handler::restore_account))
+        .route(
+            "/{id}/email/verification",
+            post(handler::request_email_verification),
+        )
+        .route("/email/confirmation", post(handler::confirm_email))
+        .route(
+            "/{id}/closure/confirmation",
+            post(handler::request_account_closure),
+        )
+        .route("/{id}/closure", post(handler::close_account))
+        .route("/{id}/export", get(handler::export_account))
+        .route(
+            "/{id}/tokens",
+            post(handler::create_api_token).get(handler::list_api_tokens),
+        )
+        .route("/{id}/tokens/{token_id}", delete(handler::revoke_api_token))
+        .route("/import", post(handler::import_account))
         .with_state(pool)
 }
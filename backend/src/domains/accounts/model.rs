@@ -1,12 +1,160 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, Row, sqlite::SqliteRow};
+
+use crate::domains::catalog::model::AssetMaster;
+use crate::domains::portfolio::model::{Asset, AssetCategory, UserAssetGrouping};
+
+/// Bump when the export document changes shape
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, utoipa::ToSchema)]
 pub struct Account {
     pub id: String,
     pub name: String,
     pub email: String,
+    /// When `email` was confirmed; None while it is unverified
+    #[serde(default)]
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// Requested new address; replaces `email` once its verification token is confirmed
+    #[serde(default)]
+    pub pending_email: Option<String>,
+    /// Bumped on every change; sent as the ETag
+    #[serde(default)]
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+/// Portable backup of everything an account owns
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AccountExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub account: Account,
+    pub categories: Vec<AssetCategory>,
+    pub groupings: Vec<UserAssetGrouping>,
+    pub holdings: Vec<Asset>,
+    /// Catalog entries referenced by groupings and holdings
+    pub catalog: Vec<AssetMaster>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AccountImportSummary {
+    pub account: Account,
+    pub categories: usize,
+    pub groupings: usize,
+    pub holdings: usize,
+    /// Catalog entries resolved to existing ones by ticker or name
+    pub catalog_matched: usize,
+    pub catalog_created: usize,
+}
+
+/// Rows owned by an account, per table
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OwnedRowCounts {
+    pub categories: u64,
+    pub groupings: u64,
+    pub holdings: u64,
+    pub rebalance_plans: u64,
+    pub rebalance_plan_items: u64,
+    pub audit_events: u64,
+}
+
+/// Issued before closing an account; shows what the closure will erase
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ClosureConfirmation {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub rows: OwnedRowCounts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AccountClosureSummary {
+    pub account_id: String,
+    pub closed_at: DateTime<Utc>,
+    /// Rows erased along with the account, including soft-deleted ones
+    pub removed: OwnedRowCounts,
+}
+
+/// What an API token may do; a token without scopes acts with the full rights of its account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Read categories, groupings, holdings and allocation
+    ReadPortfolio,
+    /// Create, change and delete holdings, including batch upserts and imports
+    WriteHoldings,
+}
+
+impl ApiTokenScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiTokenScope::ReadPortfolio => "read_portfolio",
+            ApiTokenScope::WriteHoldings => "write_holdings",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "read_portfolio" => Some(ApiTokenScope::ReadPortfolio),
+            "write_holdings" => Some(ApiTokenScope::WriteHoldings),
+            _ => None,
+        }
+    }
+}
+
+/// Personal access token for scripts; the secret itself is only shown on creation
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ApiToken {
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    /// First characters of the secret, to tell tokens apart
+    pub token_prefix: String,
+    /// Empty for full access
+    pub scopes: Vec<ApiTokenScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Refreshed at most once a minute
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> FromRow<'r, SqliteRow> for ApiToken {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let scopes: String = row.try_get("scopes")?;
+        let scopes = scopes
+            .split(',')
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| {
+                ApiTokenScope::parse(scope).ok_or_else(|| sqlx::Error::ColumnDecode {
+                    index: "scopes".into(),
+                    source: format!("unknown API token scope {scope}").into(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            account_id: row.try_get("account_id")?,
+            name: row.try_get("name")?,
+            token_prefix: row.try_get("token_prefix")?,
+            scopes,
+            expires_at: row.try_get("expires_at")?,
+            last_used_at: row.try_get("last_used_at")?,
+            revoked_at: row.try_get("revoked_at")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// Returned once on creation; only a hash of `token` is stored
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreatedApiToken {
+    /// Send as `Authorization: Bearer <token>`
+    pub token: String,
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+}
@@ -1,98 +1,433 @@
-use crate::core::database::DbPool;
+use crate::core::database::DbConn;
 use crate::core::error::{AppError, AppResult};
-use crate::domains::accounts::model::Account;
-use sqlx::query_as;
+use crate::domains::accounts::model::{Account, ApiToken, ApiTokenScope, OwnedRowCounts};
+use crate::shared::etag::precondition_failed;
+use chrono::{DateTime, Utc};
+use sqlx::{query_as, query_scalar};
+use unicode_normalization::UnicodeNormalization;
+
+/// Tables with rows owned by an account, children first, and the condition selecting
+/// the rows of the accounts matched by `{accounts}`
+const OWNED_TABLES: [(&str, &str); 6] = [
+    (
+        "rebalance_plan_items",
+        "plan_id IN (SELECT id FROM rebalance_plans WHERE account_id IN ({accounts}))",
+    ),
+    ("rebalance_plans", "account_id IN ({accounts})"),
+    ("user_asset_groupings", "account_id IN ({accounts})"),
+    ("assets", "account_id IN ({accounts})"),
+    ("asset_categories", "account_id IN ({accounts})"),
+    ("audit_events", "account_id IN ({accounts})"),
+];
+
+/// Credentials of an account, erased with it but not counted as its data
+const CREDENTIAL_TABLES: [&str; 3] = [
+    "account_closure_tokens",
+    "api_tokens",
+    "email_verification_tokens",
+];
 
 pub struct AccountRepository;
 
 impl AccountRepository {
     pub async fn create(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         name: String,
         email: String,
     ) -> AppResult<Account> {
+        let name = normalize_account_name(&name);
         sqlx::query(
             r#"
-            INSERT INTO accounts (id, name, email)
-            VALUES (?, ?, ?)
+            INSERT INTO accounts (id, name, name_key, email)
+            VALUES (?, ?, ?, ?)
             "#,
         )
         .bind(id)
-        .bind(name)
-        .bind(email)
-        .execute(pool)
-        .await?;
+        .bind(&name)
+        .bind(name.to_lowercase())
+        .bind(normalize_email(&email))
+        .execute(&mut *conn)
+        .await
+        .map_err(account_conflict)?;
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Created account not found".into()))
     }
 
-    pub async fn find_all(pool: &DbPool) -> AppResult<Vec<Account>> {
+    pub async fn find_all(conn: &mut DbConn) -> AppResult<Vec<Account>> {
         let accounts = query_as::<_, Account>(
             r#"
-            SELECT id, name, email, created_at, updated_at
+            SELECT id, name, email, email_verified_at, pending_email, version, created_at, updated_at
             FROM accounts
+            WHERE deleted_at IS NULL
             ORDER BY created_at DESC
             "#,
         )
-        .fetch_all(pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(accounts)
     }
 
-    pub async fn find_by_id(pool: &DbPool, id: &str) -> AppResult<Option<Account>> {
+    pub async fn find_by_id(conn: &mut DbConn, id: &str) -> AppResult<Option<Account>> {
         let account = query_as::<_, Account>(
             r#"
-            SELECT id, name, email, created_at, updated_at
+            SELECT id, name, email, email_verified_at, pending_email, version, created_at, updated_at
             FROM accounts
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
         Ok(account)
     }
 
+    /// Accounts, deleted ones included, written before `name_key` existed:
+    /// `(id, name, email, pending_email)`
+    pub async fn find_unkeyed(
+        conn: &mut DbConn,
+    ) -> AppResult<Vec<(String, String, String, Option<String>)>> {
+        let accounts = query_as(
+            "SELECT id, name, email, pending_email FROM accounts WHERE name_key IS NULL ORDER BY id",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(accounts)
+    }
+
+    /// `(id, name_key, email)` of every account that already has a key
+    pub async fn find_keys(conn: &mut DbConn) -> AppResult<Vec<(String, String, String)>> {
+        let keys = query_as(
+            "SELECT id, name_key, email FROM accounts WHERE name_key IS NOT NULL ORDER BY id",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Rewrites an account's name and emails in normalized form and sets its `name_key`
+    pub async fn set_normalized(
+        conn: &mut DbConn,
+        id: &str,
+        name: &str,
+        email: &str,
+        pending_email: Option<&str>,
+    ) -> AppResult<()> {
+        let name = normalize_account_name(name);
+        sqlx::query(
+            r#"
+            UPDATE accounts
+            SET name = ?, name_key = ?, email = ?, pending_email = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&name)
+        .bind(name.to_lowercase())
+        .bind(normalize_email(email))
+        .bind(pending_email.map(normalize_email))
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(account_conflict)?;
+
+        Ok(())
+    }
+
+    /// Whether another account already uses `name` or `email`, compared as normalized
+    pub async fn exists_with_name_or_email(
+        conn: &mut DbConn,
+        name: &str,
+        email: &str,
+    ) -> AppResult<bool> {
+        let account = query_as::<_, Account>(
+            r#"
+            SELECT id, name, email, email_verified_at, pending_email, version, created_at, updated_at
+            FROM accounts
+            WHERE name_key = ? OR email = ?
+            LIMIT 1
+            "#,
+        )
+        .bind(normalize_account_name(name).to_lowercase())
+        .bind(normalize_email(email))
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(account.is_some())
+    }
+
     pub async fn update(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         name: String,
         email: String,
+        version: i64,
+    ) -> AppResult<Account> {
+        let name = normalize_account_name(&name);
+        let result = sqlx::query(
+            r#"
+            UPDATE accounts
+            SET name = ?, name_key = ?, email = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND version = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(&name)
+        .bind(name.to_lowercase())
+        .bind(normalize_email(&email))
+        .bind(id)
+        .bind(version)
+        .execute(&mut *conn)
+        .await
+        .map_err(account_conflict)?;
+
+        if result.rows_affected() == 0 {
+            return match Self::find_by_id(conn, id).await? {
+                Some(current) => Err(precondition_failed(&current)),
+                None => Err(AppError::NotFound(format!(
+                    "Account with id {} not found",
+                    id
+                ))),
+            };
+        }
+
+        Self::find_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Account not found after update".into()))
+    }
+
+    /// Whether an account other than `except_id` already uses `email`
+    pub async fn exists_with_email(
+        conn: &mut DbConn,
+        email: &str,
+        except_id: &str,
+    ) -> AppResult<bool> {
+        let found: Option<String> =
+            query_scalar("SELECT id FROM accounts WHERE email = ? AND id <> ? LIMIT 1")
+                .bind(normalize_email(email))
+                .bind(except_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+        Ok(found.is_some())
+    }
+
+    pub async fn set_pending_email(
+        conn: &mut DbConn,
+        id: &str,
+        pending_email: Option<&str>,
     ) -> AppResult<Account> {
         sqlx::query(
             r#"
             UPDATE accounts
-            SET name = ?, email = ?, updated_at = CURRENT_TIMESTAMP
-            WHERE id = ?
+            SET pending_email = ?
+            WHERE id = ? AND deleted_at IS NULL
             "#,
         )
-        .bind(name)
-        .bind(email)
+        .bind(pending_email.map(normalize_email))
         .bind(id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Account not found after update".into()))
     }
 
-    pub async fn delete(pool: &DbPool, id: &str) -> AppResult<()> {
+    /// Makes `email` the verified address of the account and drops any pending change
+    pub async fn mark_email_verified(
+        conn: &mut DbConn,
+        id: &str,
+        email: &str,
+        verified_at: DateTime<Utc>,
+    ) -> AppResult<Account> {
+        sqlx::query(
+            r#"
+            UPDATE accounts
+            SET email = ?, email_verified_at = ?, pending_email = NULL
+            WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(email)
+        .bind(verified_at)
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(account_conflict)?;
+
+        Self::find_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Account not found after update".into()))
+    }
+
+    /// Replaces any earlier verification token of the account
+    pub async fn save_email_token(
+        conn: &mut DbConn,
+        id: &str,
+        token_hash: &str,
+        email: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO email_verification_tokens (account_id, token_hash, email, expires_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (account_id) DO UPDATE SET
+                token_hash = excluded.token_hash,
+                email = excluded.email,
+                expires_at = excluded.expires_at
+            "#,
+        )
+        .bind(id)
+        .bind(token_hash)
+        .bind(email)
+        .bind(expires_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Account ID and address of the unexpired verification token with this hash
+    pub async fn find_email_token(
+        conn: &mut DbConn,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> AppResult<Option<(String, String)>> {
+        let found = query_as::<_, (String, String)>(
+            r#"
+            SELECT account_id, email
+            FROM email_verification_tokens
+            WHERE token_hash = ? AND expires_at > ?
+            "#,
+        )
+        .bind(token_hash)
+        .bind(now)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(found)
+    }
+
+    pub async fn delete_email_token(conn: &mut DbConn, id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM email_verification_tokens WHERE account_id = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Marks the account deleted; the row stays until the purge job runs
+    pub async fn delete(conn: &mut DbConn, id: &str, deleted_at: DateTime<Utc>) -> AppResult<()> {
         let result = sqlx::query(
             r#"
-            DELETE FROM accounts
+            UPDATE accounts
+            SET deleted_at = ?
+            WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(deleted_at)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Account with id {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// When the account was soft-deleted, or None if it is active or missing
+    pub async fn find_deleted_at(conn: &mut DbConn, id: &str) -> AppResult<Option<DateTime<Utc>>> {
+        let deleted_at = query_scalar(
+            r#"
+            SELECT deleted_at
+            FROM accounts
+            WHERE id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(deleted_at)
+    }
+
+    pub async fn restore(conn: &mut DbConn, id: &str) -> AppResult<Account> {
+        sqlx::query(
+            r#"
+            UPDATE accounts
+            SET deleted_at = NULL
             WHERE id = ?
             "#,
         )
         .bind(id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
+        Self::find_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Account not found after restore".into()))
+    }
+
+    /// Whether the account exists, including soft-deleted ones
+    pub async fn exists_including_deleted(conn: &mut DbConn, id: &str) -> AppResult<bool> {
+        let found: Option<String> = query_scalar("SELECT id FROM accounts WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        Ok(found.is_some())
+    }
+
+    pub async fn count_owned_rows(conn: &mut DbConn, id: &str) -> AppResult<OwnedRowCounts> {
+        let mut counts = [0u64; OWNED_TABLES.len()];
+        for (count, (table, condition)) in counts.iter_mut().zip(OWNED_TABLES) {
+            let sql = format!(
+                "SELECT COUNT(*) FROM {table} WHERE {}",
+                condition.replace("{accounts}", "?")
+            );
+            let rows: i64 = query_scalar(&sql).bind(id).fetch_one(&mut *conn).await?;
+            *count = rows as u64;
+        }
+
+        Ok(owned_row_counts(counts))
+    }
+
+    /// Hard-deletes the account and every row it owns, soft-deleted or not
+    pub async fn erase(conn: &mut DbConn, id: &str) -> AppResult<OwnedRowCounts> {
+        let mut counts = [0u64; OWNED_TABLES.len()];
+        for (count, (table, condition)) in counts.iter_mut().zip(OWNED_TABLES) {
+            let sql = format!(
+                "DELETE FROM {table} WHERE {}",
+                condition.replace("{accounts}", "?")
+            );
+            *count = sqlx::query(&sql)
+                .bind(id)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+        }
+
+        for table in CREDENTIAL_TABLES {
+            sqlx::query(&format!("DELETE FROM {table} WHERE account_id = ?"))
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        let result = sqlx::query("DELETE FROM accounts WHERE id = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
         if result.rows_affected() == 0 {
             return Err(AppError::NotFound(format!(
                 "Account with id {} not found",
@@ -100,6 +435,266 @@ impl AccountRepository {
             )));
         }
 
+        Ok(owned_row_counts(counts))
+    }
+
+    /// Replaces any earlier closure token of the account
+    pub async fn save_closure_token(
+        conn: &mut DbConn,
+        id: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_closure_tokens (account_id, token_hash, expires_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT (account_id) DO UPDATE
+                SET token_hash = excluded.token_hash, expires_at = excluded.expires_at
+            "#,
+        )
+        .bind(id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&mut *conn)
+        .await?;
+
         Ok(())
     }
+
+    /// Whether `token_hash` is the hash of the account's closure token and it has not expired
+    pub async fn closure_token_matches(
+        conn: &mut DbConn,
+        id: &str,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let found: Option<String> = query_scalar(
+            r#"
+            SELECT account_id
+            FROM account_closure_tokens
+            WHERE account_id = ? AND token_hash = ? AND expires_at > ?
+            "#,
+        )
+        .bind(id)
+        .bind(token_hash)
+        .bind(now)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(found.is_some())
+    }
+
+    /// Hard-deletes accounts soft-deleted before `cutoff` together with everything they own
+    pub async fn purge_deleted_before(conn: &mut DbConn, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        const PURGED_ACCOUNTS: &str =
+            "SELECT id FROM accounts WHERE deleted_at IS NOT NULL AND deleted_at < ?";
+        for (table, condition) in OWNED_TABLES {
+            let sql = format!(
+                "DELETE FROM {table} WHERE {}",
+                condition.replace("{accounts}", PURGED_ACCOUNTS)
+            );
+            sqlx::query(&sql).bind(cutoff).execute(&mut *conn).await?;
+        }
+        for table in CREDENTIAL_TABLES {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE account_id IN ({PURGED_ACCOUNTS})"
+            ))
+            .bind(cutoff)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let result =
+            sqlx::query("DELETE FROM accounts WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+                .bind(cutoff)
+                .execute(&mut *conn)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+pub struct ApiTokenRepository;
+
+impl ApiTokenRepository {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+        name: String,
+        token_hash: &str,
+        token_prefix: &str,
+        scopes: &[ApiTokenScope],
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AppResult<ApiToken> {
+        let scopes: Vec<&str> = scopes.iter().map(|scope| scope.as_str()).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO api_tokens (id, account_id, name, token_hash, token_prefix, scopes, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .bind(name)
+        .bind(token_hash)
+        .bind(token_prefix)
+        .bind(scopes.join(","))
+        .bind(expires_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Self::find_by_id(conn, account_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Created API token not found".into()))
+    }
+
+    pub async fn find_all(conn: &mut DbConn, account_id: &str) -> AppResult<Vec<ApiToken>> {
+        let tokens = query_as::<_, ApiToken>(
+            r#"
+            SELECT id, account_id, name, token_prefix, scopes, expires_at, last_used_at, revoked_at, created_at, updated_at
+            FROM api_tokens
+            WHERE account_id = ?
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(account_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    pub async fn find_by_id(
+        conn: &mut DbConn,
+        account_id: &str,
+        id: &str,
+    ) -> AppResult<Option<ApiToken>> {
+        let token = query_as::<_, ApiToken>(
+            r#"
+            SELECT id, account_id, name, token_prefix, scopes, expires_at, last_used_at, revoked_at, created_at, updated_at
+            FROM api_tokens
+            WHERE id = ? AND account_id = ?
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Unrevoked, unexpired token with this hash whose account is not deleted
+    pub async fn find_usable_by_hash(
+        conn: &mut DbConn,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> AppResult<Option<ApiToken>> {
+        let token = query_as::<_, ApiToken>(
+            r#"
+            SELECT t.id, t.account_id, t.name, t.token_prefix, t.scopes, t.expires_at, t.last_used_at, t.revoked_at, t.created_at, t.updated_at
+            FROM api_tokens t
+            JOIN accounts a ON a.id = t.account_id
+            WHERE t.token_hash = ?
+              AND t.revoked_at IS NULL
+              AND (t.expires_at IS NULL OR t.expires_at > ?)
+              AND a.deleted_at IS NULL
+            "#,
+        )
+        .bind(token_hash)
+        .bind(now)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Records a use unless a concurrent request already did after `stale_before`
+    pub async fn touch(
+        conn: &mut DbConn,
+        id: &str,
+        used_at: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE api_tokens
+            SET last_used_at = ?
+            WHERE id = ? AND (last_used_at IS NULL OR last_used_at < ?)
+            "#,
+        )
+        .bind(used_at)
+        .bind(id)
+        .bind(stale_before)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns false when the token does not exist or was already revoked
+    pub async fn revoke(
+        conn: &mut DbConn,
+        account_id: &str,
+        id: &str,
+        revoked_at: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE api_tokens SET revoked_at = ? WHERE id = ? AND account_id = ? AND revoked_at IS NULL",
+        )
+        .bind(revoked_at)
+        .bind(id)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Trimmed and NFKC-normalized, so full-width and half-width forms are the same name.
+/// Its lowercase form is stored as `name_key`, which is unique.
+pub fn normalize_account_name(name: &str) -> String {
+    name.trim().nfkc().collect()
+}
+
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Names the field another account already holds instead of the generic unique-violation message
+fn account_conflict(err: sqlx::Error) -> AppError {
+    if let Some(db_err) = err.as_database_error()
+        && db_err.is_unique_violation()
+    {
+        let field = if db_err.message().contains("accounts.email") {
+            "email"
+        } else {
+            "name"
+        };
+        return AppError::Conflict(format!("An account with this {field} already exists"));
+    }
+    err.into()
+}
+
+fn owned_row_counts(counts: [u64; OWNED_TABLES.len()]) -> OwnedRowCounts {
+    let [
+        rebalance_plan_items,
+        rebalance_plans,
+        groupings,
+        holdings,
+        categories,
+        audit_events,
+    ] = counts;
+    OwnedRowCounts {
+        categories,
+        groupings,
+        holdings,
+        rebalance_plans,
+        rebalance_plan_items,
+        audit_events,
+    }
 }
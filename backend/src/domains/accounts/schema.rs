@@ -1,4 +1,6 @@
+use crate::domains::accounts::model::ApiTokenScope;
 use crate::shared::validation::validate_non_blank;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use validator::Validate;
 
@@ -23,3 +25,45 @@ pub struct UpdateAccountRequest {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
 }
+
+/// A changed email stays pending until it is confirmed
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct PatchAccountRequest {
+    #[validate(custom(
+        function = "validate_non_blank",
+        message = "Name cannot be empty or blank"
+    ))]
+    pub name: Option<String>,
+    #[validate(email(message = "Invalid email format"))]
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CloseAccountRequest {
+    /// Token from the confirmation endpoint; required unless confirmation is disabled
+    pub confirmation_token: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ConfirmEmailRequest {
+    /// Token from the verification mail
+    pub token: String,
+}
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct CreateApiTokenRequest {
+    /// What the token is for, e.g. "nightly holdings sync"
+    #[validate(
+        custom(
+            function = "validate_non_blank",
+            message = "Name cannot be empty or blank"
+        ),
+        length(max = 100, message = "Name must be at most 100 characters")
+    )]
+    pub name: String,
+    /// Leave empty for a token with the full rights of the account
+    #[serde(default)]
+    pub scopes: Vec<ApiTokenScope>,
+    /// Never expires when absent
+    pub expires_at: Option<DateTime<Utc>>,
+}
@@ -1,35 +1,963 @@
-use crate::core::database::DbPool;
-use crate::core::error::AppResult;
-use crate::domains::accounts::model::Account;
-use crate::domains::accounts::repository::AccountRepository;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+
+use crate::core::config::AccountsConfig;
+use crate::core::database::{DbConn, DbPool, transaction};
+use crate::core::error::{AppError, AppResult};
+use crate::core::mail;
+use crate::domains::accounts::model::{
+    Account, AccountClosureSummary, AccountExport, AccountImportSummary, ApiToken, ApiTokenScope,
+    ClosureConfirmation, CreatedApiToken, EXPORT_FORMAT_VERSION,
+};
+use crate::domains::accounts::repository::{
+    AccountRepository, ApiTokenRepository, normalize_account_name, normalize_email,
+};
+use crate::domains::audit::model::AuditEntity;
+use crate::domains::audit::service::AuditService;
+use crate::domains::catalog::model::AssetMaster;
+use crate::domains::catalog::repository::AssetMasterRepository;
+use crate::domains::portfolio::repository::{
+    AssetCategoryRepository, AssetGroupingRepository, AssetRepository,
+};
+use crate::shared::etag::ensure_version;
 use crate::shared::util::generate_id;
+use crate::shared::validation::{
+    validate_holding_amount, validate_isin, validate_non_blank, validate_target_ratio,
+    validate_ticker_symbol, validate_weight,
+};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+/// How long a closure confirmation token stays valid
+const CLOSURE_TOKEN_TTL_MINUTES: i64 = 15;
+
+/// Marks API tokens so they are easy to spot in scripts and secret scanners
+const API_TOKEN_PREFIX: &str = "gst_";
+
+/// Characters of the secret kept in clear to tell tokens apart
+const API_TOKEN_VISIBLE_CHARS: usize = 8;
+
+/// How long an email verification token stays valid
+const EMAIL_TOKEN_TTL_HOURS: i64 = 24;
+
+/// `last_used_at` of an API token is only rewritten once it is older than this, so a
+/// busy script does not turn every request into a write
+const API_TOKEN_TOUCH_INTERVAL_SECS: i64 = 60;
 
 pub struct AccountService;
 
 impl AccountService {
+    /// Normalizes the names and emails of accounts written before `name_key` existed and
+    /// returns how many were changed. This runs in Rust because SQLite cannot apply NFKC or
+    /// fold non-ASCII case. Nothing is written when two accounts would collide; the error
+    /// names them so one of each group can be renamed first.
+    pub async fn normalize_legacy(pool: &DbPool) -> AppResult<usize> {
+        transaction(pool, async |conn| {
+            let unkeyed = AccountRepository::find_unkeyed(conn).await?;
+            if unkeyed.is_empty() {
+                return Ok(0);
+            }
+
+            let mut names: HashMap<String, Vec<String>> = HashMap::new();
+            let mut emails: HashMap<String, Vec<String>> = HashMap::new();
+            for (id, name_key, email) in AccountRepository::find_keys(conn).await? {
+                names.entry(name_key).or_default().push(id.clone());
+                emails.entry(normalize_email(&email)).or_default().push(id);
+            }
+            for (id, name, email, _) in &unkeyed {
+                let name_key = normalize_account_name(name).to_lowercase();
+                names.entry(name_key).or_default().push(id.clone());
+                emails
+                    .entry(normalize_email(email))
+                    .or_default()
+                    .push(id.clone());
+            }
+
+            let mut collisions: Vec<String> = names
+                .values()
+                .chain(emails.values())
+                .filter(|ids| ids.len() > 1)
+                .map(|ids| ids.join(", "))
+                .collect();
+            if !collisions.is_empty() {
+                collisions.sort();
+                return Err(AppError::Conflict(format!(
+                    "Accounts would share a name or email once normalized; change one of each \
+                     group first: {}",
+                    collisions.join("; ")
+                )));
+            }
+
+            for (id, name, email, pending_email) in &unkeyed {
+                AccountRepository::set_normalized(conn, id, name, email, pending_email.as_deref())
+                    .await?;
+            }
+            Ok(unkeyed.len())
+        })
+        .await
+    }
+
+    /// Creates the account unverified and mails a verification token to its address
     pub async fn create(pool: &DbPool, name: String, email: String) -> AppResult<Account> {
         let id = generate_id();
-        AccountRepository::create(pool, &id, name, email).await
+        let token = generate_secret();
+        let account = transaction(pool, async |conn| {
+            let account = AccountRepository::create(conn, &id, name, email).await?;
+            AuditService::created(conn, Some(&id), AuditEntity::Account, &id, &account).await?;
+            issue_email_token(conn, &id, &account.email, &token).await?;
+            Ok(account)
+        })
+        .await?;
+        send_verification_mail(&account.email, &token).await;
+        Ok(account)
     }
 
     pub async fn get_all(pool: &DbPool) -> AppResult<Vec<Account>> {
-        AccountRepository::find_all(pool).await
+        let mut conn = pool.acquire().await?;
+        AccountRepository::find_all(&mut conn).await
     }
 
     pub async fn get_by_id(pool: &DbPool, id: &str) -> AppResult<Option<Account>> {
-        AccountRepository::find_by_id(pool, id).await
+        let mut conn = pool.acquire().await?;
+        AccountRepository::find_by_id(&mut conn, id).await
     }
 
+    /// Changes the given fields and keeps the rest; a new email only becomes pending.
+    /// Starting an email change needs `by_token`, i.e. a caller that proved itself with the
+    /// account's own API token, because the `X-Account-ID` header alone proves nothing.
     pub async fn update(
         pool: &DbPool,
         id: &str,
-        name: String,
-        email: String,
+        name: Option<String>,
+        email: Option<String>,
+        by_token: bool,
+        expected_version: Option<i64>,
     ) -> AppResult<Account> {
-        AccountRepository::update(pool, id, name, email).await
+        let token = generate_secret();
+        let (account, verify) = transaction(pool, async |conn| {
+            let before = AccountRepository::find_by_id(conn, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Account not found".into()))?;
+            ensure_version(expected_version, before.version, &before)?;
+
+            let name = name.unwrap_or_else(|| before.name.clone());
+            let mut account =
+                AccountRepository::update(conn, id, name, before.email.clone(), before.version)
+                    .await?;
+            let mut verify = None;
+            match email.as_deref().map(normalize_email) {
+                // A new address only becomes `email` once it is confirmed
+                Some(email) if email != before.email => {
+                    if !by_token {
+                        return Err(AppError::Forbidden(
+                            "Changing the email requires an API token of the account".into(),
+                        ));
+                    }
+                    if AccountRepository::exists_with_email(conn, &email, id).await? {
+                        return Err(AppError::Conflict(
+                            "An account with this email already exists".into(),
+                        ));
+                    }
+                    account = AccountRepository::set_pending_email(conn, id, Some(&email)).await?;
+                    issue_email_token(conn, id, &email, &token).await?;
+                    verify = Some(email);
+                }
+                // Asking for the current address again cancels a pending change
+                Some(_) if before.pending_email.is_some() => {
+                    account = AccountRepository::set_pending_email(conn, id, None).await?;
+                    AccountRepository::delete_email_token(conn, id).await?;
+                }
+                _ => {}
+            }
+            AuditService::updated(
+                conn,
+                Some(id),
+                AuditEntity::Account,
+                id,
+                Some(&before),
+                &account,
+            )
+            .await?;
+            Ok((account, verify))
+        })
+        .await?;
+
+        if let Some(email) = verify {
+            send_verification_mail(&email, &token).await;
+        }
+        Ok(account)
+    }
+
+    /// Mails a fresh verification token for the pending address, or for the current
+    /// one while it is unverified; earlier tokens stop working
+    pub async fn request_email_verification(pool: &DbPool, id: &str) -> AppResult<()> {
+        let token = generate_secret();
+        let email = transaction(pool, async |conn| {
+            let account = AccountRepository::find_by_id(conn, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Account not found".into()))?;
+            let email = match account.pending_email {
+                Some(pending_email) => pending_email,
+                None if account.email_verified_at.is_none() => account.email,
+                None => return Err(AppError::Conflict("Email is already verified".into())),
+            };
+            issue_email_token(conn, id, &email, &token).await?;
+            Ok(email)
+        })
+        .await?;
+        send_verification_mail(&email, &token).await;
+        Ok(())
+    }
+
+    /// Verifies the address the token was mailed to, switching `email` over when it
+    /// was a pending change. Tokens work once.
+    pub async fn confirm_email(pool: &DbPool, token: &str) -> AppResult<Account> {
+        let now = Utc::now();
+        transaction(pool, async |conn| {
+            let invalid = || {
+                let mut errors = ValidationErrors::new();
+                errors.add(
+                    "token",
+                    ValidationError::new("invalid_verification_token").with_message(
+                        "Verification token is unknown, expired or already used".into(),
+                    ),
+                );
+                AppError::Validation(errors)
+            };
+            let (id, email) = AccountRepository::find_email_token(conn, &hash_token(token), now)
+                .await?
+                .ok_or_else(invalid)?;
+            let before = AccountRepository::find_by_id(conn, &id)
+                .await?
+                .ok_or_else(invalid)?;
+            let current = before.email == email && before.pending_email.is_none();
+            if !current && before.pending_email.as_deref() != Some(email.as_str()) {
+                return Err(invalid());
+            }
+
+            AccountRepository::delete_email_token(conn, &id).await?;
+            let account = AccountRepository::mark_email_verified(conn, &id, &email, now).await?;
+            AuditService::updated(
+                conn,
+                Some(&id),
+                AuditEntity::Account,
+                &id,
+                Some(&before),
+                &account,
+            )
+            .await?;
+            Ok(account)
+        })
+        .await
     }
 
+    /// Soft-deletes the account and every category, grouping and holding it owns
     pub async fn delete(pool: &DbPool, id: &str) -> AppResult<()> {
-        AccountRepository::delete(pool, id).await
+        let deleted_at = Utc::now();
+        transaction(pool, async |conn| {
+            let before = AccountRepository::find_by_id(conn, id).await?;
+            AccountRepository::delete(conn, id, deleted_at).await?;
+            AuditService::deleted(conn, Some(id), AuditEntity::Account, id, before.as_ref())
+                .await?;
+            AssetGroupingRepository::delete_by_account(conn, id, deleted_at).await?;
+            AssetRepository::delete_by_account(conn, id, deleted_at).await?;
+            AssetCategoryRepository::delete_by_account(conn, id, deleted_at).await
+        })
+        .await
+    }
+
+    /// Restores the account and the portfolio rows that were deleted with it
+    pub async fn restore(pool: &DbPool, id: &str) -> AppResult<Account> {
+        transaction(pool, async |conn| {
+            let deleted_at = AccountRepository::find_deleted_at(conn, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Deleted account not found".into()))?;
+            let account = AccountRepository::restore(conn, id).await?;
+            AssetCategoryRepository::restore_by_account(conn, id, deleted_at).await?;
+            AssetGroupingRepository::restore_by_account(conn, id, deleted_at).await?;
+            AssetRepository::restore_by_account(conn, id, deleted_at).await?;
+            AuditService::restored(conn, Some(id), AuditEntity::Account, id, &account).await?;
+            Ok(account)
+        })
+        .await
+    }
+
+    /// Issues a short-lived token that `close` must be called with, along with a
+    /// count of the rows the closure would erase
+    pub async fn request_closure(pool: &DbPool, id: &str) -> AppResult<ClosureConfirmation> {
+        let token = generate_id();
+        let expires_at = Utc::now() + Duration::minutes(CLOSURE_TOKEN_TTL_MINUTES);
+        transaction(pool, async |conn| {
+            if !AccountRepository::exists_including_deleted(conn, id).await? {
+                return Err(AppError::NotFound(format!(
+                    "Account with id {} not found",
+                    id
+                )));
+            }
+            AccountRepository::save_closure_token(conn, id, &hash_token(&token), expires_at)
+                .await?;
+            let rows = AccountRepository::count_owned_rows(conn, id).await?;
+            Ok(ClosureConfirmation {
+                token,
+                expires_at,
+                rows,
+            })
+        })
+        .await
+    }
+
+    /// Permanently erases the account and everything it owns in one transaction.
+    /// Unlike `delete`, this cannot be undone.
+    pub async fn close(
+        pool: &DbPool,
+        id: &str,
+        confirmation_token: Option<String>,
+    ) -> AppResult<AccountClosureSummary> {
+        let closed_at = Utc::now();
+        transaction(pool, async |conn| {
+            if closure_requires_confirmation() {
+                let confirmed = match confirmation_token.as_deref() {
+                    Some(token) => {
+                        AccountRepository::closure_token_matches(
+                            conn,
+                            id,
+                            &hash_token(token),
+                            closed_at,
+                        )
+                        .await?
+                    }
+                    None => false,
+                };
+                if !confirmed {
+                    let mut errors = ValidationErrors::new();
+                    errors.add(
+                        "confirmation_token",
+                        ValidationError::new("invalid_confirmation_token").with_message(
+                            "Request a closure confirmation and pass its unexpired token".into(),
+                        ),
+                    );
+                    return Err(AppError::Validation(errors));
+                }
+            }
+
+            let removed = AccountRepository::erase(conn, id).await?;
+            // The account's own history is erased with it; keep only an anonymous marker
+            AuditService::deleted::<Account>(conn, None, AuditEntity::Account, id, None).await?;
+            Ok(AccountClosureSummary {
+                account_id: id.to_string(),
+                closed_at,
+                removed,
+            })
+        })
+        .await
+    }
+
+    pub async fn export(pool: &DbPool, id: &str) -> AppResult<AccountExport> {
+        let mut conn = pool.acquire().await?;
+        let account = AccountRepository::find_by_id(&mut conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Account not found".into()))?;
+        let categories = AssetCategoryRepository::find_by_account(&mut conn, id).await?;
+        let groupings = AssetGroupingRepository::find_by_account(&mut conn, id).await?;
+        let holdings = AssetRepository::find_by_account(&mut conn, id).await?;
+
+        let mut asset_master_ids: Vec<&str> = groupings
+            .iter()
+            .map(|grouping| grouping.asset_master_id.as_str())
+            .chain(
+                holdings
+                    .iter()
+                    .map(|holding| holding.asset_master_id.as_str()),
+            )
+            .collect();
+        asset_master_ids.sort_unstable();
+        asset_master_ids.dedup();
+
+        let mut catalog = Vec::with_capacity(asset_master_ids.len());
+        for asset_master_id in asset_master_ids {
+            if let Some(asset) =
+                AssetMasterRepository::find_by_id(&mut conn, asset_master_id).await?
+            {
+                catalog.push(asset);
+            }
+        }
+
+        Ok(AccountExport {
+            version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            account,
+            categories,
+            groupings,
+            holdings,
+            catalog,
+        })
+    }
+
+    /// Recreates an exported account under fresh IDs. Catalog entries are matched by
+    /// ticker or name and created when missing.
+    pub async fn import(pool: &DbPool, document: AccountExport) -> AppResult<AccountImportSummary> {
+        if document.version != EXPORT_FORMAT_VERSION {
+            return Err(import_error(
+                "version",
+                "unsupported_version",
+                format!("Unsupported export version {}", document.version),
+            ));
+        }
+        check_import_document(&document)?;
+
+        transaction(pool, async |conn| {
+            let source = &document.account;
+            if AccountRepository::exists_with_name_or_email(conn, &source.name, &source.email)
+                .await?
+            {
+                return Err(import_error(
+                    "account",
+                    "account_exists",
+                    "An account with the same name or email already exists".into(),
+                ));
+            }
+            let account = AccountRepository::create(
+                conn,
+                &generate_id(),
+                source.name.clone(),
+                source.email.clone(),
+            )
+            .await?;
+            AuditService::created(
+                conn,
+                Some(&account.id),
+                AuditEntity::Account,
+                &account.id,
+                &account,
+            )
+            .await?;
+
+            let mut summary = AccountImportSummary {
+                account,
+                categories: 0,
+                groupings: 0,
+                holdings: 0,
+                catalog_matched: 0,
+                catalog_created: 0,
+            };
+            let account_id = summary.account.id.clone();
+
+            let mut asset_ids: HashMap<&str, String> = HashMap::new();
+            for entry in &document.catalog {
+                let (asset_id, created) = Self::resolve_catalog_entry(conn, entry).await?;
+                if created {
+                    summary.catalog_created += 1;
+                } else {
+                    summary.catalog_matched += 1;
+                }
+                asset_ids.insert(entry.id.as_str(), asset_id);
+            }
+            let asset_id_of = |old_id: &str| {
+                asset_ids.get(old_id).cloned().ok_or_else(|| {
+                    import_error(
+                        "catalog",
+                        "missing_catalog_entry",
+                        format!("Catalog entry {} is not included in the export", old_id),
+                    )
+                })
+            };
+
+            let mut category_ids: HashMap<&str, String> = HashMap::new();
+            for category in &document.categories {
+                let created = AssetCategoryRepository::create(
+                    conn,
+                    &generate_id(),
+                    &account_id,
+                    category.name.clone(),
+                    category.target_ratio,
+                )
+                .await?;
+                AuditService::created(
+                    conn,
+                    Some(&account_id),
+                    AuditEntity::Category,
+                    &created.id,
+                    &created,
+                )
+                .await?;
+                category_ids.insert(category.id.as_str(), created.id);
+                summary.categories += 1;
+            }
+
+            for grouping in &document.groupings {
+                let category_id = category_ids
+                    .get(grouping.category_id.as_str())
+                    .cloned()
+                    .ok_or_else(|| {
+                        import_error(
+                            "categories",
+                            "missing_category",
+                            format!(
+                                "Category {} is not included in the export",
+                                grouping.category_id
+                            ),
+                        )
+                    })?;
+                let created = AssetGroupingRepository::create(
+                    conn,
+                    &generate_id(),
+                    &account_id,
+                    asset_id_of(&grouping.asset_master_id)?,
+                    category_id,
+                    grouping.weight,
+                )
+                .await?;
+                AuditService::created(
+                    conn,
+                    Some(&account_id),
+                    AuditEntity::Grouping,
+                    &created.id,
+                    &created,
+                )
+                .await?;
+                summary.groupings += 1;
+            }
+
+            for holding in &document.holdings {
+                let created = AssetRepository::create(
+                    conn,
+                    &generate_id(),
+                    &account_id,
+                    asset_id_of(&holding.asset_master_id)?,
+                    holding.current_amount,
+                    holding.is_liability,
+                )
+                .await?;
+                AuditService::created(
+                    conn,
+                    Some(&account_id),
+                    AuditEntity::Holding,
+                    &created.id,
+                    &created,
+                )
+                .await?;
+                summary.holdings += 1;
+            }
+
+            Ok(summary)
+        })
+        .await
+    }
+
+    /// Returns the local ID for an exported catalog entry and whether it had to be created
+    async fn resolve_catalog_entry(
+        conn: &mut DbConn,
+        entry: &AssetMaster,
+    ) -> AppResult<(String, bool)> {
+        if let Some(isin) = entry.isin.as_deref()
+            && let Some(asset) = AssetMasterRepository::find_by_isin(conn, isin).await?
+        {
+            return Ok((asset.id, false));
+        }
+        if let Some(ticker) = entry.ticker_symbol.as_deref()
+            && let Some(asset) = AssetMasterRepository::find_by_ticker(conn, ticker).await?
+        {
+            return Ok((asset.id, false));
+        }
+        if let Some(asset) = AssetMasterRepository::find_by_name(conn, &entry.name).await? {
+            return Ok((asset.id, false));
+        }
+
+        let asset = AssetMaster {
+            id: generate_id(),
+            name: entry.name.clone(),
+            ticker_symbol: entry.ticker_symbol.clone(),
+            isin: entry.isin.clone(),
+            version: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let created = AssetMasterRepository::create(conn, &asset).await?;
+        AuditService::created(conn, None, AuditEntity::AssetMaster, &created.id, &created).await?;
+        Ok((created.id, true))
+    }
+}
+
+/// Runs an imported document through the validators of the live API so an import cannot
+/// store what the endpoints would refuse. Problems are reported by path, e.g.
+/// `holdings[2].current_amount`.
+fn check_import_document(document: &AccountExport) -> AppResult<()> {
+    let mut errors = ValidationErrors::new();
+
+    let catalog = check_items(&document.catalog, |entry, item| {
+        check(
+            item,
+            "name",
+            validate_non_blank(&entry.name),
+            "Name cannot be empty or blank",
+        );
+        if let Some(ticker) = entry.ticker_symbol.as_deref() {
+            check(
+                item,
+                "ticker_symbol",
+                validate_ticker_symbol(ticker),
+                "Ticker must be uppercase letters and digits with an optional exchange suffix",
+            );
+        }
+        if let Some(isin) = entry.isin.as_deref() {
+            check(
+                item,
+                "isin",
+                validate_isin(isin),
+                "ISIN must be 12 characters with a valid check digit",
+            );
+        }
+    });
+    add_list(&mut errors, "catalog", catalog);
+
+    let categories = check_items(&document.categories, |category, item| {
+        check(
+            item,
+            "name",
+            validate_non_blank(&category.name),
+            "Name cannot be empty or blank",
+        );
+        check(
+            item,
+            "target_ratio",
+            validate_target_ratio(&category.target_ratio),
+            "Target ratio must be between 0 and 100 with at most 2 decimal places",
+        );
+    });
+    add_list(&mut errors, "categories", categories);
+
+    // Same limits as `AssetGroupingService`: each weight in (0, 100], at most 100 per asset
+    let mut totals: HashMap<&str, Decimal> = HashMap::new();
+    for grouping in &document.groupings {
+        *totals
+            .entry(grouping.asset_master_id.as_str())
+            .or_insert(Decimal::ZERO) += grouping.weight;
+    }
+    let groupings = check_items(&document.groupings, |grouping, item| {
+        check(
+            item,
+            "weight",
+            validate_weight(&grouping.weight),
+            "Weight must be greater than 0 and at most 100",
+        );
+        if totals[grouping.asset_master_id.as_str()] > Decimal::ONE_HUNDRED {
+            item.add(
+                "weight",
+                ValidationError::new("weight_total").with_message(
+                    format!(
+                        "Weights of asset {} exceed 100 in total",
+                        grouping.asset_master_id
+                    )
+                    .into(),
+                ),
+            );
+        }
+    });
+    add_list(&mut errors, "groupings", groupings);
+
+    let holdings = check_items(&document.holdings, |holding, item| {
+        check(
+            item,
+            "current_amount",
+            validate_holding_amount(&holding.current_amount),
+            "Amount must be zero or positive with at most 4 decimal places",
+        );
+    });
+    add_list(&mut errors, "holdings", holdings);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Unprocessable(errors))
+    }
+}
+
+/// Errors of each item that has any, keyed by its index
+fn check_items<T>(
+    items: &[T],
+    mut check_item: impl FnMut(&T, &mut ValidationErrors),
+) -> BTreeMap<usize, Box<ValidationErrors>> {
+    let mut failed = BTreeMap::new();
+    for (index, value) in items.iter().enumerate() {
+        let mut item = ValidationErrors::new();
+        check_item(value, &mut item);
+        if !item.is_empty() {
+            failed.insert(index, Box::new(item));
+        }
+    }
+    failed
+}
+
+fn check(
+    item: &mut ValidationErrors,
+    field: &'static str,
+    result: Result<(), ValidationError>,
+    message: &'static str,
+) {
+    if let Err(err) = result {
+        item.add(field, err.with_message(message.into()));
+    }
+}
+
+fn add_list(
+    errors: &mut ValidationErrors,
+    field: &'static str,
+    items: BTreeMap<usize, Box<ValidationErrors>>,
+) {
+    if !items.is_empty() {
+        errors
+            .errors_mut()
+            .insert(field.into(), ValidationErrorsKind::List(items));
+    }
+}
+
+fn import_error(field: &'static str, code: &'static str, message: String) -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        field,
+        ValidationError::new(code).with_message(message.into()),
+    );
+    AppError::Validation(errors)
+}
+
+static SETTINGS: OnceLock<AccountsConfig> = OnceLock::new();
+
+/// Keeps the account settings; the defaults apply until this is called
+pub fn install(config: &AccountsConfig) {
+    let _ = SETTINGS.set(config.clone());
+}
+
+fn closure_requires_confirmation() -> bool {
+    SETTINGS
+        .get_or_init(AccountsConfig::default)
+        .closure_requires_confirmation
+}
+
+pub struct ApiTokenService;
+
+impl ApiTokenService {
+    /// Issues a token for the account; the secret is returned here and never again
+    pub async fn create(
+        pool: &DbPool,
+        account_id: &str,
+        name: String,
+        mut scopes: Vec<ApiTokenScope>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AppResult<CreatedApiToken> {
+        if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            let mut errors = ValidationErrors::new();
+            errors.add(
+                "expires_at",
+                ValidationError::new("expires_in_past")
+                    .with_message("Expiry must be in the future".into()),
+            );
+            return Err(AppError::Validation(errors));
+        }
+        scopes.sort_by_key(|scope| scope.as_str());
+        scopes.dedup();
+
+        let id = generate_id();
+        let token = format!("{API_TOKEN_PREFIX}{}", generate_secret());
+        let token_prefix = &token[..API_TOKEN_PREFIX.len() + API_TOKEN_VISIBLE_CHARS];
+        transaction(pool, async |conn| {
+            if AccountRepository::find_by_id(conn, account_id)
+                .await?
+                .is_none()
+            {
+                return Err(AppError::NotFound("Account not found".into()));
+            }
+            let api_token = ApiTokenRepository::create(
+                conn,
+                &id,
+                account_id,
+                name,
+                &hash_token(&token),
+                token_prefix,
+                &scopes,
+                expires_at,
+            )
+            .await?;
+            AuditService::created(
+                conn,
+                Some(account_id),
+                AuditEntity::ApiToken,
+                &id,
+                &api_token,
+            )
+            .await?;
+            Ok(CreatedApiToken {
+                token: token.clone(),
+                api_token,
+            })
+        })
+        .await
+    }
+
+    /// Every token of the account, including expired and revoked ones
+    pub async fn get_all(pool: &DbPool, account_id: &str) -> AppResult<Vec<ApiToken>> {
+        let mut conn = pool.acquire().await?;
+        ApiTokenRepository::find_all(&mut conn, account_id).await
+    }
+
+    pub async fn revoke(pool: &DbPool, account_id: &str, id: &str) -> AppResult<ApiToken> {
+        let revoked_at = Utc::now();
+        transaction(pool, async |conn| {
+            let before = ApiTokenRepository::find_by_id(conn, account_id, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("API token not found".into()))?;
+            if !ApiTokenRepository::revoke(conn, account_id, id, revoked_at).await? {
+                return Err(AppError::Conflict("API token is already revoked".into()));
+            }
+            let after = ApiTokenRepository::find_by_id(conn, account_id, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("API token not found".into()))?;
+            AuditService::updated(
+                conn,
+                Some(account_id),
+                AuditEntity::ApiToken,
+                id,
+                Some(&before),
+                &after,
+            )
+            .await?;
+            Ok(after)
+        })
+        .await
+    }
+
+    /// Resolves a presented secret to its token, recording the use;
+    /// None when it is unknown, revoked, expired or its account is deleted
+    pub async fn authenticate(pool: &DbPool, token: &str) -> AppResult<Option<ApiToken>> {
+        let now = Utc::now();
+        let mut conn = pool.acquire().await?;
+        let found =
+            ApiTokenRepository::find_usable_by_hash(&mut conn, &hash_token(token), now).await?;
+        let stale_before = now - Duration::seconds(API_TOKEN_TOUCH_INTERVAL_SECS);
+        if let Some(api_token) = &found
+            && api_token
+                .last_used_at
+                .is_none_or(|last_used_at| last_used_at < stale_before)
+        {
+            ApiTokenRepository::touch(&mut conn, &api_token.id, now, stale_before).await?;
+        }
+        Ok(found)
+    }
+}
+
+/// Stores the hash of `token` as the account's only valid verification token for `email`
+async fn issue_email_token(conn: &mut DbConn, id: &str, email: &str, token: &str) -> AppResult<()> {
+    let expires_at = Utc::now() + Duration::hours(EMAIL_TOKEN_TTL_HOURS);
+    AccountRepository::save_email_token(conn, id, &hash_token(token), email, expires_at).await
+}
+
+/// Runs after the token is committed; a failed delivery is logged and can be retried
+/// through the resend endpoint
+async fn send_verification_mail(email: &str, token: &str) {
+    let body = format!(
+        "Confirm {email} as the address of your Geld;Steer account by sending this token \
+         within {EMAIL_TOKEN_TTL_HOURS} hours:\n\n    {token}\n\n\
+         POST /api/v1/accounts/email/confirmation with {{\"token\": \"{token}\"}}"
+    );
+    if let Err(err) = mail::send(email, "Confirm your email address", body, Some(token)).await {
+        tracing::error!(error = %err, to = %email, "failed to send the verification mail");
+    }
+}
+
+/// Two IDs back to back, long enough to be unguessable
+fn generate_secret() -> String {
+    format!("{}{}", generate_id(), generate_id())
+}
+
+/// Tokens are long random strings, so a fast unsalted hash is enough to keep the
+/// stored values useless to someone reading the database
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use rust_decimal::Decimal;
+
+    use super::AccountService;
+    use crate::core::database::test_pool;
+    use crate::domains::portfolio::service::{
+        AssetCategoryService, AssetGroupingService, AssetService,
+    };
+
+    #[tokio::test]
+    async fn import_refuses_what_the_api_would_refuse() {
+        let pool = test_pool().await;
+        let account = AccountService::create(&pool, "owner".into(), "owner@example.com".into())
+            .await
+            .expect("account is created");
+        sqlx::query(
+            "INSERT INTO asset_master (id, name, ticker_symbol) VALUES ('fund', 'Fund', 'VTI')",
+        )
+        .execute(&pool)
+        .await
+        .expect("catalog asset is created");
+        let category =
+            AssetCategoryService::create(&pool, &account.id, "Equity".into(), Decimal::from(60))
+                .await
+                .expect("category is created");
+        AssetGroupingService::create(
+            &pool,
+            &account.id,
+            "fund".into(),
+            category.id,
+            Decimal::from(100),
+        )
+        .await
+        .expect("grouping is created");
+        AssetService::create(
+            &pool,
+            &account.id,
+            "fund".into(),
+            Decimal::from(1000),
+            false,
+        )
+        .await
+        .expect("holding is created");
+
+        let mut document = AccountService::export(&pool, &account.id)
+            .await
+            .expect("account exports");
+        document.account.name = "copy".into();
+        document.account.email = "copy@example.com".into();
+        document.catalog[0].ticker_symbol = Some("vti".into());
+        document.categories[0].target_ratio = Decimal::from(150);
+        document.groupings[0].weight = Decimal::ZERO;
+        document.holdings[0].current_amount = Decimal::new(-12345, 5);
+
+        let err = AccountService::import(&pool, document)
+            .await
+            .expect_err("invalid document is refused");
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body is readable");
+        let problem: serde_json::Value = serde_json::from_slice(&body).expect("body is JSON");
+        let mut paths: Vec<&str> = problem["errors"]
+            .as_array()
+            .expect("errors are listed")
+            .iter()
+            .filter_map(|error| error["field"].as_str())
+            .collect();
+        paths.dedup();
+        assert_eq!(
+            paths,
+            [
+                "catalog[0].ticker_symbol",
+                "categories[0].target_ratio",
+                "groupings[0].weight",
+                "holdings[0].current_amount",
+            ]
+        );
     }
 }
@@ -0,0 +1,119 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::core::database::DbPool;
+use crate::core::error::AppResult;
+use crate::core::maintenance;
+use crate::domains::admin::model::{BackupInfo, MaintenanceStatus, PurgeSummary};
+use crate::domains::admin::schema::UpdateMaintenanceRequest;
+use crate::domains::admin::service::{BackupService, PurgeService};
+use crate::middleware::extractor::JsonBody;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/backups",
+    responses(
+        (status = 200, description = "Database backups, newest first", body = Vec<BackupInfo>),
+        (status = 401, description = "Admin token missing or wrong"),
+        (status = 403, description = "Admin API disabled")
+    ),
+    tag = "admin"
+)]
+pub async fn list_backups() -> AppResult<impl IntoResponse> {
+    let backups = BackupService::list().await?;
+    Ok(Json(backups))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/backups",
+    responses(
+        (status = 201, description = "Backup taken; old backups beyond the retention count are removed", body = BackupInfo),
+        (status = 401, description = "Admin token missing or wrong"),
+        (status = 403, description = "Admin API disabled")
+    ),
+    tag = "admin"
+)]
+pub async fn create_backup(State(pool): State<DbPool>) -> AppResult<impl IntoResponse> {
+    let backup = BackupService::create(&pool).await?;
+    Ok((StatusCode::CREATED, Json(backup)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/backups/{name}/restore",
+    params(
+        ("name" = String, Path, description = "Backup file name")
+    ),
+    responses(
+        (status = 200, description = "Database restored from the backup", body = BackupInfo),
+        (status = 404, description = "Backup not found"),
+        (status = 409, description = "A server on the database is not in maintenance mode, or the backup schema differs"),
+        (status = 401, description = "Admin token missing or wrong"),
+        (status = 403, description = "Admin API disabled")
+    ),
+    tag = "admin"
+)]
+pub async fn restore_backup(
+    State(pool): State<DbPool>,
+    Path(name): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let backup = BackupService::restore(&pool, &name).await?;
+    Ok(Json(backup))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/maintenance",
+    responses(
+        (status = 200, description = "Current maintenance mode", body = MaintenanceStatus),
+        (status = 401, description = "Admin token missing or wrong"),
+        (status = 403, description = "Admin API disabled")
+    ),
+    tag = "admin"
+)]
+pub async fn get_maintenance() -> impl IntoResponse {
+    Json(MaintenanceStatus {
+        enabled: maintenance::is_enabled(),
+    })
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/maintenance",
+    request_body = UpdateMaintenanceRequest,
+    responses(
+        (status = 200, description = "Maintenance mode updated", body = MaintenanceStatus),
+        (status = 409, description = "A restore is still running"),
+        (status = 401, description = "Admin token missing or wrong"),
+        (status = 403, description = "Admin API disabled")
+    ),
+    tag = "admin"
+)]
+pub async fn update_maintenance(
+    JsonBody(payload): JsonBody<UpdateMaintenanceRequest>,
+) -> AppResult<impl IntoResponse> {
+    maintenance::set_enabled(payload.enabled)?;
+    Ok(Json(MaintenanceStatus {
+        enabled: payload.enabled,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/purge",
+    responses(
+        (status = 200, description = "Soft-deleted rows past the retention period removed", body = PurgeSummary),
+        (status = 401, description = "Admin token missing or wrong"),
+        (status = 403, description = "Admin API disabled")
+    ),
+    tag = "admin"
+)]
+pub async fn purge_deleted(State(pool): State<DbPool>) -> AppResult<impl IntoResponse> {
+    let summary = PurgeService::run(&pool).await?;
+    Ok(Json(summary))
+}
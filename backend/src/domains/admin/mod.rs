@@ -0,0 +1,27 @@
+pub mod handler;
+pub mod model;
+pub mod repository;
+pub mod schema;
+pub mod service;
+
+use crate::core::database::DbPool;
+
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+pub fn admin_routes(pool: DbPool) -> Router {
+    Router::new()
+        .route(
+            "/backups",
+            get(handler::list_backups).post(handler::create_backup),
+        )
+        .route("/backups/{name}/restore", post(handler::restore_backup))
+        .route(
+            "/maintenance",
+            get(handler::get_maintenance).put(handler::update_maintenance),
+        )
+        .route("/purge", post(handler::purge_deleted))
+        .with_state(pool)
+}
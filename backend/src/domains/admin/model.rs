@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BackupInfo {
+    /// File name inside the backup directory
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+}
+
+/// Rows hard-deleted by a purge run
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PurgeSummary {
+    pub accounts: u64,
+    pub categories: u64,
+    pub groupings: u64,
+    pub holdings: u64,
+}
@@ -0,0 +1,74 @@
+use sqlx::Connection;
+
+use crate::core::database::DbConn;
+use crate::core::error::{AppError, AppResult};
+
+pub struct BackupRepository;
+
+impl BackupRepository {
+    /// Writes a consistent snapshot of the live database to `path`
+    pub async fn vacuum_into(conn: &mut DbConn, path: &str) -> AppResult<()> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path)
+            .execute(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces every table's rows with the contents of the backup at `path` in one
+    /// transaction, so open connections never observe a half-restored database.
+    pub async fn restore_from(conn: &mut DbConn, path: &str) -> AppResult<()> {
+        sqlx::query("ATTACH DATABASE ? AS backup")
+            .bind(path)
+            .execute(&mut *conn)
+            .await?;
+        let result = Self::copy_attached(conn).await;
+        sqlx::query("DETACH DATABASE backup")
+            .execute(&mut *conn)
+            .await?;
+        result
+    }
+
+    async fn copy_attached(conn: &mut DbConn) -> AppResult<()> {
+        if Self::schema(conn, "main").await? != Self::schema(conn, "backup").await? {
+            return Err(AppError::Conflict(
+                "Backup schema does not match the current database".into(),
+            ));
+        }
+
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM backup.sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut tx = conn.begin().await?;
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await?;
+        // Clear everything before copying so ON DELETE CASCADE cannot remove restored rows
+        for table in &tables {
+            sqlx::query(&format!(r#"DELETE FROM main."{table}""#))
+                .execute(&mut *tx)
+                .await?;
+        }
+        for table in &tables {
+            sqlx::query(&format!(
+                r#"INSERT INTO main."{table}" SELECT * FROM backup."{table}""#
+            ))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn schema(conn: &mut DbConn, database: &str) -> AppResult<Vec<(String, String)>> {
+        let schema = sqlx::query_as(&format!(
+            "SELECT name, sql FROM {database}.sqlite_master WHERE name NOT LIKE 'sqlite_%' AND sql IS NOT NULL ORDER BY name"
+        ))
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(schema)
+    }
+}
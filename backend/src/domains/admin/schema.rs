@@ -0,0 +1,6 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateMaintenanceRequest {
+    pub enabled: bool,
+}
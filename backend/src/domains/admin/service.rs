@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+use crate::core::config::AdminConfig;
+use crate::core::database::{DbPool, transaction};
+use crate::core::error::{AppError, AppResult};
+use crate::core::maintenance;
+use crate::domains::accounts::repository::AccountRepository;
+use crate::domains::admin::model::{BackupInfo, PurgeSummary};
+use crate::domains::admin::repository::BackupRepository;
+use crate::domains::portfolio::repository::{
+    AssetCategoryRepository, AssetGroupingRepository, AssetRepository,
+};
+
+/// File name pattern of generated backups; the timestamp doubles as the sort key
+const BACKUP_NAME_FORMAT: &str = "backup-%Y%m%dT%H%M%S%.3fZ.db";
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+pub struct BackupService;
+
+impl BackupService {
+    /// Takes a hot backup and prunes backups beyond the retention count
+    pub async fn create(pool: &DbPool) -> AppResult<BackupInfo> {
+        let dir = backup_dir();
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let name = Utc::now().format(BACKUP_NAME_FORMAT).to_string();
+        let path = dir.join(&name);
+        let mut conn = pool.acquire().await?;
+        BackupRepository::vacuum_into(&mut conn, &path.to_string_lossy()).await?;
+
+        Self::rotate(backup_retention()).await?;
+        Self::find(&name)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Created backup not found".into()))
+    }
+
+    /// Backups in the backup directory, newest first
+    pub async fn list() -> AppResult<Vec<BackupInfo>> {
+        let dir = backup_dir();
+        let mut backups = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(backups),
+            Err(err) => return Err(err.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Ok(created_at) = NaiveDateTime::parse_from_str(&name, BACKUP_NAME_FORMAT) else {
+                continue;
+            };
+            backups.push(BackupInfo {
+                name,
+                created_at: DateTime::from_naive_utc_and_offset(created_at, Utc),
+                size_bytes: entry.metadata().await?.len(),
+            });
+        }
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+        Ok(backups)
+    }
+
+    /// Restores the named backup. Only allowed while every server on the database is in
+    /// maintenance mode or stopped, so no writes race it.
+    pub async fn restore(pool: &DbPool, name: &str) -> AppResult<BackupInfo> {
+        let _lock = maintenance::lock_for_restore()?;
+        let backup = Self::find(name)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Backup not found".into()))?;
+
+        let path = backup_dir().join(&backup.name);
+        let mut conn = pool.acquire().await?;
+        BackupRepository::restore_from(&mut conn, &path.to_string_lossy()).await?;
+        Ok(backup)
+    }
+
+    /// Deletes all but the newest `keep` backups, returning the removed ones
+    pub async fn rotate(keep: usize) -> AppResult<Vec<BackupInfo>> {
+        let dir = backup_dir();
+        let removed: Vec<BackupInfo> = Self::list().await?.into_iter().skip(keep).collect();
+        for backup in &removed {
+            tokio::fs::remove_file(dir.join(&backup.name)).await?;
+        }
+        Ok(removed)
+    }
+
+    async fn find(name: &str) -> AppResult<Option<BackupInfo>> {
+        Ok(Self::list()
+            .await?
+            .into_iter()
+            .find(|backup| backup.name == name))
+    }
+}
+
+pub struct PurgeService;
+
+impl PurgeService {
+    /// Hard-deletes rows that have been soft-deleted for longer than the retention period
+    pub async fn run(pool: &DbPool) -> AppResult<PurgeSummary> {
+        let cutoff = Utc::now() - Duration::days(soft_delete_retention_days());
+        transaction(pool, async |conn| {
+            // Children first so foreign keys hold at every step
+            Ok(PurgeSummary {
+                groupings: AssetGroupingRepository::purge_deleted_before(conn, cutoff).await?,
+                holdings: AssetRepository::purge_deleted_before(conn, cutoff).await?,
+                categories: AssetCategoryRepository::purge_deleted_before(conn, cutoff).await?,
+                accounts: AccountRepository::purge_deleted_before(conn, cutoff).await?,
+            })
+        })
+        .await
+    }
+
+    /// Runs the purge hourly in the background for the lifetime of the server
+    pub fn spawn_schedule(pool: DbPool) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PURGE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(err) = Self::run(&pool).await {
+                    tracing::error!(error = ?err, "scheduled purge failed");
+                }
+            }
+        });
+    }
+}
+
+static SETTINGS: OnceLock<AdminConfig> = OnceLock::new();
+
+/// Keeps the backup and purge settings; the defaults apply until this is called
+pub fn install(config: &AdminConfig) {
+    let _ = SETTINGS.set(config.clone());
+}
+
+fn settings() -> &'static AdminConfig {
+    SETTINGS.get_or_init(AdminConfig::default)
+}
+
+fn backup_dir() -> PathBuf {
+    PathBuf::from(&settings().backup_dir)
+}
+
+fn backup_retention() -> usize {
+    settings().backup_retention
+}
+
+fn soft_delete_retention_days() -> i64 {
+    settings().soft_delete_retention_days.into()
+}
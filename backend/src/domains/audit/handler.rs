@@ -0,0 +1,57 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+};
+use validator::Validate;
+
+use crate::core::database::DbPool;
+use crate::core::error::AppResult;
+use crate::domains::audit::model::AuditEvent;
+use crate::domains::audit::schema::AuditEventQuery;
+use crate::domains::audit::service::AuditService;
+use crate::middleware::extractor::get_account_id;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/audit/events",
+    params(AuditEventQuery),
+    responses(
+        (status = 200, description = "Recorded mutations of the caller's account, newest first", body = [AuditEvent]),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified")
+    ),
+    tag = "audit"
+)]
+pub async fn list_audit_events(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Query(query): Query<AuditEventQuery>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    query.validate()?;
+    let events = AuditService::list(&pool, &account_id, &query).await?;
+    Ok(Json(events))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/audit/catalog-events",
+    params(AuditEventQuery),
+    responses(
+        (status = 200, description = "Recorded mutations of the shared catalog, newest first", body = [AuditEvent]),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "X-Admin-Token header missing or wrong"),
+        (status = 403, description = "Admin API is disabled")
+    ),
+    tag = "audit"
+)]
+pub async fn list_catalog_events(
+    State(pool): State<DbPool>,
+    Query(query): Query<AuditEventQuery>,
+) -> AppResult<impl IntoResponse> {
+    query.validate()?;
+    let events = AuditService::list_catalog(&pool, &query).await?;
+    Ok(Json(events))
+}
@@ -0,0 +1,24 @@
+pub mod handler;
+pub mod model;
+pub mod repository;
+pub mod schema;
+pub mod service;
+
+use std::sync::Arc;
+
+use crate::core::database::DbPool;
+use crate::middleware::admin::require_admin;
+
+use axum::{Router, middleware::from_fn_with_state, routing::get};
+
+/// Catalog events have no owning account, so listing them needs the admin token
+pub fn audit_routes(pool: DbPool, admin_token: Option<Arc<str>>) -> Router {
+    Router::new()
+        .route("/events", get(handler::list_audit_events))
+        .route(
+            "/catalog-events",
+            get(handler::list_catalog_events)
+                .route_layer(from_fn_with_state(admin_token, require_admin)),
+        )
+        .with_state(pool)
+}
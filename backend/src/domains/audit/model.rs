@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row, sqlite::SqliteRow};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AuditEntity {
+    Account,
+    AssetMaster,
+    /// The whole breakdown of one catalog entry; `entity_id` is the asset master ID
+    AssetComposition,
+    Category,
+    Grouping,
+    Holding,
+    ApiToken,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AuditEvent {
+    pub id: String,
+    /// Account owning the changed entity; None for catalog changes
+    pub account_id: Option<String>,
+    /// Account of the request that made the change, from its API token or X-Account-ID
+    pub actor: Option<String>,
+    pub entity_type: AuditEntity,
+    pub entity_id: String,
+    pub action: AuditAction,
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl<'r> FromRow<'r, SqliteRow> for AuditEvent {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            account_id: row.try_get("account_id")?,
+            actor: row.try_get("actor")?,
+            entity_type: row.try_get("entity_type")?,
+            entity_id: row.try_get("entity_id")?,
+            action: row.try_get("action")?,
+            before: decode_json(row, "before_json")?,
+            after: decode_json(row, "after_json")?,
+            occurred_at: row.try_get("occurred_at")?,
+        })
+    }
+}
+
+fn decode_json(row: &SqliteRow, column: &str) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let text: Option<String> = row.try_get(column)?;
+    text.map(|text| serde_json::from_str(&text))
+        .transpose()
+        .map_err(|err| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(err),
+        })
+}
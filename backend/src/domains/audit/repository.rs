@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::core::database::DbConn;
+use crate::core::error::AppResult;
+use crate::domains::audit::model::{AuditAction, AuditEntity, AuditEvent};
+use crate::domains::audit::schema::AuditEventQuery;
+
+/// Default page size of the event query
+const DEFAULT_LIMIT: u32 = 100;
+
+pub struct AuditRepository;
+
+impl AuditRepository {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: Option<&str>,
+        actor: Option<&str>,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        action: AuditAction,
+        before_json: Option<String>,
+        after_json: Option<String>,
+        occurred_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_events
+                (id, account_id, actor, entity_type, entity_id, action, before_json, after_json, occurred_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .bind(actor)
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(action)
+        .bind(before_json)
+        .bind(after_json)
+        .bind(occurred_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Events of one account, or of the catalog when `account_id` is None, newest first
+    pub async fn find(
+        conn: &mut DbConn,
+        account_id: Option<&str>,
+        filter: &AuditEventQuery,
+    ) -> AppResult<Vec<AuditEvent>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT id, account_id, actor, entity_type, entity_id, action, before_json, after_json, occurred_at
+            FROM audit_events
+            "#,
+        );
+        match account_id {
+            Some(account_id) => query.push(" WHERE account_id = ").push_bind(account_id),
+            None => query.push(" WHERE account_id IS NULL"),
+        };
+        if let Some(entity_type) = filter.entity_type {
+            query.push(" AND entity_type = ").push_bind(entity_type);
+        }
+        if let Some(entity_id) = &filter.entity_id {
+            query.push(" AND entity_id = ").push_bind(entity_id);
+        }
+        if let Some(from) = filter.from {
+            query.push(" AND occurred_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND occurred_at < ").push_bind(to);
+        }
+        query
+            .push(" ORDER BY occurred_at DESC, rowid DESC LIMIT ")
+            .push_bind(filter.limit.unwrap_or(DEFAULT_LIMIT));
+
+        let events = query
+            .build_query_as::<AuditEvent>()
+            .fetch_all(&mut *conn)
+            .await?;
+
+        Ok(events)
+    }
+}
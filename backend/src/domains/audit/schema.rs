@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::domains::audit::model::AuditEntity;
+
+#[derive(Deserialize, Validate, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditEventQuery {
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<String>,
+    /// Inclusive lower bound of `occurred_at`
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound of `occurred_at`
+    pub to: Option<DateTime<Utc>>,
+    /// Defaults to 100
+    #[validate(range(min = 1, max = 1000, message = "Limit must be between 1 and 1000"))]
+    pub limit: Option<u32>,
+}
@@ -0,0 +1,139 @@
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::core::database::{DbConn, DbPool};
+use crate::core::error::{AppError, AppResult};
+use crate::domains::audit::model::{AuditAction, AuditEntity, AuditEvent};
+use crate::domains::audit::repository::AuditRepository;
+use crate::domains::audit::schema::AuditEventQuery;
+use crate::middleware::audit::current_actor;
+use crate::shared::util::generate_id;
+
+pub struct AuditService;
+
+impl AuditService {
+    /// Records one mutation. Call it on the connection of the mutating transaction so the
+    /// event is committed or rolled back together with the change.
+    pub async fn record<T: Serialize>(
+        conn: &mut DbConn,
+        account_id: Option<&str>,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        action: AuditAction,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> AppResult<()> {
+        AuditRepository::create(
+            conn,
+            &generate_id(),
+            account_id,
+            current_actor().as_deref(),
+            entity_type,
+            entity_id,
+            action,
+            before.map(to_json).transpose()?,
+            after.map(to_json).transpose()?,
+            Utc::now(),
+        )
+        .await
+    }
+
+    pub async fn created<T: Serialize>(
+        conn: &mut DbConn,
+        account_id: Option<&str>,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        after: &T,
+    ) -> AppResult<()> {
+        Self::record(
+            conn,
+            account_id,
+            entity_type,
+            entity_id,
+            AuditAction::Create,
+            None,
+            Some(after),
+        )
+        .await
+    }
+
+    pub async fn updated<T: Serialize>(
+        conn: &mut DbConn,
+        account_id: Option<&str>,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        before: Option<&T>,
+        after: &T,
+    ) -> AppResult<()> {
+        Self::record(
+            conn,
+            account_id,
+            entity_type,
+            entity_id,
+            AuditAction::Update,
+            before,
+            Some(after),
+        )
+        .await
+    }
+
+    pub async fn deleted<T: Serialize>(
+        conn: &mut DbConn,
+        account_id: Option<&str>,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        before: Option<&T>,
+    ) -> AppResult<()> {
+        Self::record(
+            conn,
+            account_id,
+            entity_type,
+            entity_id,
+            AuditAction::Delete,
+            before,
+            None,
+        )
+        .await
+    }
+
+    pub async fn restored<T: Serialize>(
+        conn: &mut DbConn,
+        account_id: Option<&str>,
+        entity_type: AuditEntity,
+        entity_id: &str,
+        after: &T,
+    ) -> AppResult<()> {
+        Self::record(
+            conn,
+            account_id,
+            entity_type,
+            entity_id,
+            AuditAction::Restore,
+            None,
+            Some(after),
+        )
+        .await
+    }
+
+    pub async fn list(
+        pool: &DbPool,
+        account_id: &str,
+        filter: &AuditEventQuery,
+    ) -> AppResult<Vec<AuditEvent>> {
+        let mut conn = pool.acquire().await?;
+        AuditRepository::find(&mut conn, Some(account_id), filter).await
+    }
+
+    /// Catalog changes belong to no account and are only visible to admins
+    pub async fn list_catalog(
+        pool: &DbPool,
+        filter: &AuditEventQuery,
+    ) -> AppResult<Vec<AuditEvent>> {
+        let mut conn = pool.acquire().await?;
+        AuditRepository::find(&mut conn, None, filter).await
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> AppResult<String> {
+    serde_json::to_string(value).map_err(|err| AppError::Io(err.into()))
+}
@@ -1,15 +1,21 @@
 use axum::{
     Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 
+use validator::Validate;
+
 use crate::core::database::DbPool;
 use crate::core::error::{AppError, AppResult};
-use crate::domains::catalog::model::AssetMaster;
-use crate::domains::catalog::schema::{CreateAssetRequest, UpdateAssetRequest};
+use crate::domains::catalog::model::{AssetComposition, AssetMaster};
+use crate::domains::catalog::schema::{
+    CreateAssetRequest, PatchAssetRequest, ReplaceCompositionRequest, UpdateAssetRequest,
+};
 use crate::domains::catalog::service::AssetMasterService;
+use crate::middleware::extractor::JsonBody;
+use crate::shared::etag::{etag, parse_if_match};
 
 #[utoipa::path(
     post,
@@ -17,16 +23,19 @@ use crate::domains::catalog::service::AssetMasterService;
     request_body = CreateAssetRequest,
     responses(
         (status = 201, description = "Asset created successfully", body = AssetMaster),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 409, description = "Another asset has the same name or ISIN")
     ),
     tag = "catalog"
 )]
 pub async fn create_asset(
     State(pool): State<DbPool>,
-    Json(payload): Json<CreateAssetRequest>,
+    JsonBody(payload): JsonBody<CreateAssetRequest>,
 ) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
     let asset: AssetMaster =
-        AssetMasterService::create(&pool, payload.name, payload.ticker_symbol).await?;
+        AssetMasterService::create(&pool, payload.name, payload.ticker_symbol, payload.isin)
+            .await?;
     Ok((StatusCode::CREATED, Json(asset)))
 }
 
@@ -50,7 +59,7 @@ pub async fn list_assets(State(pool): State<DbPool>) -> AppResult<impl IntoRespo
         ("id" = String, Path, description = "Asset ID")
     ),
     responses(
-        (status = 200, description = "Asset found", body = AssetMaster),
+        (status = 200, description = "Asset found", body = AssetMaster, headers(("ETag" = String, description = "Current version of the asset"))),
         (status = 404, description = "Asset not found")
     ),
     tag = "catalog"
@@ -62,30 +71,81 @@ pub async fn get_asset(
     let asset: AssetMaster = AssetMasterService::get_by_id(&pool, &id)
         .await?
         .ok_or(AppError::NotFound("Asset not found".into()))?;
-    Ok(Json(asset))
+    Ok((etag(asset.version), Json(asset)))
 }
 
 #[utoipa::path(
     put,
     path = "/api/v1/catalog/{id}",
     params(
-        ("id" = String, Path, description = "Asset ID")
+        ("id" = String, Path, description = "Asset ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
     ),
     request_body = UpdateAssetRequest,
     responses(
-        (status = 200, description = "Asset updated successfully", body = AssetMaster),
-        (status = 404, description = "Asset not found")
+        (status = 200, description = "Asset updated successfully", body = AssetMaster, headers(("ETag" = String, description = "Current version of the asset"))),
+        (status = 400, description = "Bad request"),
+        (status = 404, description = "Asset not found"),
+        (status = 409, description = "Another asset has the same name or ISIN"),
+        (status = 412, description = "The asset changed since the If-Match version; the body holds its current state"),
     ),
     tag = "catalog"
 )]
 pub async fn update_asset(
     State(pool): State<DbPool>,
+    headers: HeaderMap,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateAssetRequest>,
+    JsonBody(payload): JsonBody<UpdateAssetRequest>,
 ) -> AppResult<impl IntoResponse> {
-    let asset: AssetMaster =
-        AssetMasterService::update(&pool, &id, payload.name, payload.ticker_symbol).await?;
-    Ok(Json(asset))
+    let expected_version = parse_if_match(&headers)?;
+    payload.validate()?;
+    let asset: AssetMaster = AssetMasterService::update(
+        &pool,
+        &id,
+        Some(payload.name),
+        Some(payload.ticker_symbol),
+        Some(payload.isin),
+        expected_version,
+    )
+    .await?;
+    Ok((etag(asset.version), Json(asset)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/catalog/{id}",
+    params(
+        ("id" = String, Path, description = "Asset ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
+    ),
+    request_body = PatchAssetRequest,
+    responses(
+        (status = 200, description = "Present fields of the asset updated", body = AssetMaster, headers(("ETag" = String, description = "Current version of the asset"))),
+        (status = 400, description = "Bad request"),
+        (status = 404, description = "Asset not found"),
+        (status = 409, description = "Another asset has the same name or ISIN"),
+        (status = 412, description = "The asset changed since the If-Match version; the body holds its current state"),
+    ),
+    tag = "catalog"
+)]
+pub async fn patch_asset(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<PatchAssetRequest>,
+) -> AppResult<impl IntoResponse> {
+    let expected_version = parse_if_match(&headers)?;
+    payload.validate()?;
+    let asset: AssetMaster = AssetMasterService::update(
+        &pool,
+        &id,
+        payload.name,
+        payload.ticker_symbol,
+        payload.isin,
+        expected_version,
+    )
+    .await?;
+    Ok((etag(asset.version), Json(asset)))
 }
 
 #[utoipa::path(
@@ -95,8 +155,9 @@ pub async fn update_asset(
         ("id" = String, Path, description = "Asset ID")
     ),
     responses(
-        (status = 204, description = "Asset deleted successfully"),
-        (status = 404, description = "Asset not found")
+        (status = 204, description = "Asset and its composition deleted"),
+        (status = 404, description = "Asset not found"),
+        (status = 409, description = "Asset is still used by holdings, groupings or rebalance plans")
     ),
     tag = "catalog"
 )]
@@ -107,3 +168,54 @@ pub async fn delete_asset(
     AssetMasterService::delete(&pool, &id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/catalog/{id}/composition",
+    params(
+        ("id" = String, Path, description = "Asset ID")
+    ),
+    responses(
+        (status = 200, description = "Default breakdown of the asset", body = [AssetComposition]),
+        (status = 404, description = "Asset not found")
+    ),
+    tag = "catalog"
+)]
+pub async fn get_composition(
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let composition = AssetMasterService::get_composition(&pool, &id).await?;
+    Ok(Json(composition))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/catalog/{id}/composition",
+    params(
+        ("id" = String, Path, description = "Asset ID")
+    ),
+    request_body = ReplaceCompositionRequest,
+    responses(
+        (status = 200, description = "Breakdown replaced successfully", body = [AssetComposition]),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "X-Admin-Token header missing or wrong"),
+        (status = 403, description = "Admin API is disabled"),
+        (status = 404, description = "Asset not found")
+    ),
+    tag = "catalog"
+)]
+pub async fn replace_composition(
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<ReplaceCompositionRequest>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+    let items = payload
+        .items
+        .into_iter()
+        .map(|item| (item.dimension, item.label, item.weight))
+        .collect();
+    let composition = AssetMasterService::replace_composition(&pool, &id, items).await?;
+    Ok(Json(composition))
+}
@@ -4,21 +4,34 @@ pub mod repository;
 pub mod schema;
 pub mod service;
 
+use std::sync::Arc;
+
 use crate::core::database::DbPool;
+use crate::middleware::admin::require_admin;
 
 use axum::{
     Router,
-    routing::{get, post},
+    middleware::from_fn_with_state,
+    routing::{get, post, put},
 };
 
-pub fn catalog_routes(pool: DbPool) -> Router {
+/// Compositions are maintained by catalog admins, so replacing one needs the admin token
+pub fn catalog_routes(pool: DbPool, admin_token: Option<Arc<str>>) -> Router {
     Router::new()
         .route("/", post(handler::create_asset).get(handler::list_assets))
         .route(
             "/{id}",
             get(handler::get_asset)
                 .put(handler::update_asset)
+                .patch(handler::patch_asset)
                 .delete(handler::delete_asset),
         )
+        .route(
+            "/{id}/composition",
+            get(handler::get_composition).merge(
+                put(handler::replace_composition)
+                    .route_layer(from_fn_with_state(admin_token, require_admin)),
+            ),
+        )
         .with_state(pool)
 }
@@ -1,12 +1,59 @@
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, Row, sqlite::SqliteRow};
+
+use crate::shared::decimal::decode_decimal;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, utoipa::ToSchema)]
 pub struct AssetMaster {
     pub id: String,
     pub name: String,
     pub ticker_symbol: Option<String>,
+    /// International Securities Identification Number, unique when set
+    #[serde(default)]
+    pub isin: Option<String>,
+    /// Bumped on every change; sent as the ETag
+    #[serde(default)]
+    pub version: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Axis along which a fund's holdings are broken down
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum CompositionDimension {
+    Region,
+    AssetClass,
+    Sector,
+}
+
+/// One line of a fund's default breakdown (e.g. region "US" = 62.5%)
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AssetComposition {
+    pub id: String,
+    pub asset_master_id: String,
+    pub dimension: CompositionDimension,
+    pub label: String,
+    pub weight: Decimal,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+impl<'r> FromRow<'r, SqliteRow> for AssetComposition {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            asset_master_id: row.try_get("asset_master_id")?,
+            dimension: row.try_get("dimension")?,
+            label: row.try_get("label")?,
+            weight: decode_decimal(row, "weight")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
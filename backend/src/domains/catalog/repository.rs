@@ -1,86 +1,166 @@
-use crate::core::database::DbPool;
+use crate::core::database::DbConn;
 use crate::core::error::{AppError, AppResult};
-use crate::domains::catalog::model::AssetMaster;
-use sqlx::query_as;
+use crate::domains::catalog::model::{AssetComposition, AssetMaster, CompositionDimension};
+use crate::shared::etag::precondition_failed;
+use rust_decimal::Decimal;
+use sqlx::{query_as, query_scalar};
 
 pub struct AssetMasterRepository;
 
 impl AssetMasterRepository {
-    pub async fn create(pool: &DbPool, asset: &AssetMaster) -> AppResult<AssetMaster> {
+    pub async fn create(conn: &mut DbConn, asset: &AssetMaster) -> AppResult<AssetMaster> {
         sqlx::query(
             r#"
-            INSERT INTO asset_master (id, name, ticker_symbol)
-            VALUES (?, ?, ?)
+            INSERT INTO asset_master (id, name, ticker_symbol, isin)
+            VALUES (?, ?, ?, ?)
             "#,
         )
         .bind(&asset.id)
         .bind(&asset.name)
         .bind(&asset.ticker_symbol)
-        .execute(pool)
-        .await?;
+        .bind(&asset.isin)
+        .execute(&mut *conn)
+        .await
+        .map_err(asset_conflict)?;
 
-        Self::find_by_id(pool, &asset.id)
+        Self::find_by_id(conn, &asset.id)
             .await?
             .ok_or_else(|| AppError::NotFound("Created asset not found".into()))
     }
 
-    pub async fn find_all(pool: &DbPool) -> AppResult<Vec<AssetMaster>> {
+    pub async fn find_all(conn: &mut DbConn) -> AppResult<Vec<AssetMaster>> {
         let assets = query_as::<_, AssetMaster>(
             r#"
-            SELECT id, name, ticker_symbol, created_at, updated_at
+            SELECT id, name, ticker_symbol, isin, version, created_at, updated_at
             FROM asset_master
             ORDER BY created_at DESC
             "#,
         )
-        .fetch_all(pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(assets)
     }
 
-    pub async fn find_by_id(pool: &DbPool, id: &str) -> AppResult<Option<AssetMaster>> {
+    pub async fn find_by_id(conn: &mut DbConn, id: &str) -> AppResult<Option<AssetMaster>> {
         let asset = query_as::<_, AssetMaster>(
             r#"
-            SELECT id, name, ticker_symbol, created_at, updated_at
+            SELECT id, name, ticker_symbol, isin, version, created_at, updated_at
             FROM asset_master
             WHERE id = ?
             "#,
         )
         .bind(id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(asset)
+    }
+
+    pub async fn find_by_name(conn: &mut DbConn, name: &str) -> AppResult<Option<AssetMaster>> {
+        let asset = query_as::<_, AssetMaster>(
+            r#"
+            SELECT id, name, ticker_symbol, isin, version, created_at, updated_at
+            FROM asset_master
+            WHERE name = ?
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(asset)
+    }
+
+    pub async fn find_by_isin(conn: &mut DbConn, isin: &str) -> AppResult<Option<AssetMaster>> {
+        let asset = query_as::<_, AssetMaster>(
+            r#"
+            SELECT id, name, ticker_symbol, isin, version, created_at, updated_at
+            FROM asset_master
+            WHERE isin = ?
+            "#,
+        )
+        .bind(isin)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(asset)
+    }
+
+    pub async fn find_by_ticker(
+        conn: &mut DbConn,
+        ticker_symbol: &str,
+    ) -> AppResult<Option<AssetMaster>> {
+        let asset = query_as::<_, AssetMaster>(
+            r#"
+            SELECT id, name, ticker_symbol, isin, version, created_at, updated_at
+            FROM asset_master
+            WHERE ticker_symbol = ? COLLATE NOCASE
+            "#,
+        )
+        .bind(ticker_symbol)
+        .fetch_optional(&mut *conn)
         .await?;
 
         Ok(asset)
     }
 
     pub async fn update(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         name: &str,
         ticker_symbol: Option<String>,
+        isin: Option<String>,
+        version: i64,
     ) -> AppResult<AssetMaster> {
-        sqlx::query(
+        let result = sqlx::query(
             r#"
             UPDATE asset_master
-            SET name = ?, ticker_symbol = ?
-            WHERE id = ?
+            SET name = ?, ticker_symbol = ?, isin = ?
+            WHERE id = ? AND version = ?
             "#,
         )
         .bind(name)
         .bind(ticker_symbol)
+        .bind(isin)
         .bind(id)
-        .execute(pool)
-        .await?;
+        .bind(version)
+        .execute(&mut *conn)
+        .await
+        .map_err(asset_conflict)?;
 
-        Self::find_by_id(pool, id)
+        if result.rows_affected() == 0 {
+            return match Self::find_by_id(conn, id).await? {
+                Some(current) => Err(precondition_failed(&current)),
+                None => Err(AppError::NotFound("Asset not found".into())),
+            };
+        }
+
+        Self::find_by_id(conn, id)
             .await?
             .ok_or(AppError::NotFound("Asset not found after update".into()))
     }
 
-    pub async fn delete(pool: &DbPool, id: &str) -> AppResult<()> {
+    /// Whether any holding, grouping or rebalance plan, in trash or not, refers to the asset
+    pub async fn is_referenced(conn: &mut DbConn, id: &str) -> AppResult<bool> {
+        let referenced = query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM assets WHERE asset_master_id = ?1)
+                OR EXISTS (SELECT 1 FROM user_asset_groupings WHERE asset_master_id = ?1)
+                OR EXISTS (SELECT 1 FROM rebalance_plan_items WHERE asset_master_id = ?1)
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(referenced)
+    }
+
+    pub async fn delete(conn: &mut DbConn, id: &str) -> AppResult<()> {
         let result = sqlx::query("DELETE FROM asset_master WHERE id = ?")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *conn)
             .await?;
 
         if result.rows_affected() == 0 {
@@ -92,4 +172,91 @@ impl AssetMasterRepository {
 
         Ok(())
     }
+
+    pub async fn find_composition(
+        conn: &mut DbConn,
+        asset_master_id: &str,
+    ) -> AppResult<Vec<AssetComposition>> {
+        let composition = query_as::<_, AssetComposition>(
+            r#"
+            SELECT id, asset_master_id, dimension, label, weight, created_at, updated_at
+            FROM asset_compositions
+            WHERE asset_master_id = ?
+            ORDER BY dimension, label
+            "#,
+        )
+        .bind(asset_master_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(composition)
+    }
+
+    pub async fn find_compositions_by_dimension(
+        conn: &mut DbConn,
+        dimension: CompositionDimension,
+    ) -> AppResult<Vec<AssetComposition>> {
+        let compositions = query_as::<_, AssetComposition>(
+            r#"
+            SELECT id, asset_master_id, dimension, label, weight, created_at, updated_at
+            FROM asset_compositions
+            WHERE dimension = ?
+            ORDER BY asset_master_id, label
+            "#,
+        )
+        .bind(dimension)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(compositions)
+    }
+
+    pub async fn delete_composition(conn: &mut DbConn, asset_master_id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM asset_compositions WHERE asset_master_id = ?")
+            .bind(asset_master_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_composition_item(
+        conn: &mut DbConn,
+        id: &str,
+        asset_master_id: &str,
+        dimension: CompositionDimension,
+        label: String,
+        weight: Decimal,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO asset_compositions (id, asset_master_id, dimension, label, weight)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(asset_master_id)
+        .bind(dimension)
+        .bind(label)
+        .bind(weight.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Names the field another asset already holds instead of the generic unique-violation message
+fn asset_conflict(err: sqlx::Error) -> AppError {
+    if let Some(db_err) = err.as_database_error()
+        && db_err.is_unique_violation()
+    {
+        let field = if db_err.message().contains("asset_master.isin") {
+            "ISIN"
+        } else {
+            "name"
+        };
+        return AppError::Conflict(format!("An asset with this {field} already exists"));
+    }
+    err.into()
 }
@@ -1,13 +1,125 @@
-use serde::Deserialize;
+use std::collections::HashMap;
 
-#[derive(Deserialize, utoipa::ToSchema)]
+use crate::domains::catalog::model::CompositionDimension;
+use crate::shared::validation::{
+    validate_isin, validate_non_blank, validate_ticker_symbol, validate_weight,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer};
+use validator::{Validate, ValidationError};
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
 pub struct CreateAssetRequest {
+    #[validate(
+        custom(
+            function = "validate_non_blank",
+            message = "Name cannot be empty or blank"
+        ),
+        length(max = 255, message = "Name must be at most 255 characters")
+    )]
     pub name: String,
+    #[validate(custom(
+        function = "validate_ticker_symbol",
+        message = "Ticker must be uppercase letters and digits with an optional exchange suffix, e.g. 7203.T"
+    ))]
     pub ticker_symbol: Option<String>,
+    #[validate(custom(
+        function = "validate_isin",
+        message = "ISIN must be 12 characters with a valid check digit"
+    ))]
+    pub isin: Option<String>,
 }
 
-#[derive(Deserialize, utoipa::ToSchema)]
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
 pub struct UpdateAssetRequest {
+    #[validate(
+        custom(
+            function = "validate_non_blank",
+            message = "Name cannot be empty or blank"
+        ),
+        length(max = 255, message = "Name must be at most 255 characters")
+    )]
     pub name: String,
+    #[validate(custom(
+        function = "validate_ticker_symbol",
+        message = "Ticker must be uppercase letters and digits with an optional exchange suffix, e.g. 7203.T"
+    ))]
     pub ticker_symbol: Option<String>,
+    #[validate(custom(
+        function = "validate_isin",
+        message = "ISIN must be 12 characters with a valid check digit"
+    ))]
+    pub isin: Option<String>,
+}
+
+/// Absent fields are kept; `null` clears the ticker or ISIN
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct PatchAssetRequest {
+    #[validate(
+        custom(
+            function = "validate_non_blank",
+            message = "Name cannot be empty or blank"
+        ),
+        length(max = 255, message = "Name must be at most 255 characters")
+    )]
+    pub name: Option<String>,
+    #[validate(custom(
+        function = "validate_ticker_symbol",
+        message = "Ticker must be uppercase letters and digits with an optional exchange suffix, e.g. 7203.T"
+    ))]
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    pub ticker_symbol: Option<Option<String>>,
+    #[validate(custom(
+        function = "validate_isin",
+        message = "ISIN must be 12 characters with a valid check digit"
+    ))]
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    pub isin: Option<Option<String>>,
+}
+
+/// Maps a present field, `null` included, to `Some` so it differs from an absent one
+fn present<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct CompositionItem {
+    pub dimension: CompositionDimension,
+    #[validate(custom(
+        function = "validate_non_blank",
+        message = "Label cannot be empty or blank"
+    ))]
+    pub label: String,
+    #[validate(custom(
+        function = "validate_weight",
+        message = "Weight must be greater than 0 and at most 100"
+    ))]
+    pub weight: Decimal,
+}
+
+/// Replaces the whole breakdown of an asset. Weights must sum to 100 per dimension.
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+#[validate(schema(function = "validate_composition_totals"))]
+pub struct ReplaceCompositionRequest {
+    #[validate(nested)]
+    pub items: Vec<CompositionItem>,
+}
+
+fn validate_composition_totals(request: &ReplaceCompositionRequest) -> Result<(), ValidationError> {
+    let mut totals: HashMap<CompositionDimension, Decimal> = HashMap::new();
+    for item in &request.items {
+        *totals.entry(item.dimension).or_insert(Decimal::ZERO) += item.weight;
+    }
+
+    if totals.values().any(|total| *total != Decimal::ONE_HUNDRED) {
+        return Err(ValidationError::new("weight_total")
+            .with_message("Weights must sum to 100 for each dimension".into()));
+    }
+
+    Ok(())
 }
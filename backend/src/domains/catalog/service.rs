@@ -1,8 +1,12 @@
-use crate::core::database::DbPool;
-use crate::core::error::AppResult;
-use crate::domains::catalog::model::AssetMaster;
+use crate::core::database::{DbPool, transaction};
+use crate::core::error::{AppError, AppResult};
+use crate::domains::audit::model::AuditEntity;
+use crate::domains::audit::service::AuditService;
+use crate::domains::catalog::model::{AssetComposition, AssetMaster, CompositionDimension};
 use crate::domains::catalog::repository::AssetMasterRepository;
+use crate::shared::etag::ensure_version;
 use crate::shared::util::generate_id;
+use rust_decimal::Decimal;
 
 pub struct AssetMasterService;
 
@@ -11,36 +15,130 @@ impl AssetMasterService {
         pool: &DbPool,
         name: String,
         ticker_symbol: Option<String>,
+        isin: Option<String>,
     ) -> AppResult<AssetMaster> {
         let id = generate_id();
         let asset = AssetMaster {
             id,
             name,
             ticker_symbol,
+            isin,
+            version: 1,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
-        AssetMasterRepository::create(pool, &asset).await
+        transaction(pool, async |conn| {
+            let created = AssetMasterRepository::create(conn, &asset).await?;
+            AuditService::created(conn, None, AuditEntity::AssetMaster, &created.id, &created)
+                .await?;
+            Ok(created)
+        })
+        .await
     }
 
     pub async fn get_all(pool: &DbPool) -> AppResult<Vec<AssetMaster>> {
-        AssetMasterRepository::find_all(pool).await
+        let mut conn = pool.acquire().await?;
+        AssetMasterRepository::find_all(&mut conn).await
     }
 
     pub async fn get_by_id(pool: &DbPool, id: &str) -> AppResult<Option<AssetMaster>> {
-        AssetMasterRepository::find_by_id(pool, id).await
+        let mut conn = pool.acquire().await?;
+        AssetMasterRepository::find_by_id(&mut conn, id).await
     }
 
     pub async fn update(
         pool: &DbPool,
         id: &str,
-        name: String,
-        ticker_symbol: Option<String>,
+        name: Option<String>,
+        ticker_symbol: Option<Option<String>>,
+        isin: Option<Option<String>>,
+        expected_version: Option<i64>,
     ) -> AppResult<AssetMaster> {
-        AssetMasterRepository::update(pool, id, &name, ticker_symbol).await
+        transaction(pool, async |conn| {
+            let before = AssetMasterRepository::find_by_id(conn, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Asset not found".into()))?;
+            ensure_version(expected_version, before.version, &before)?;
+
+            let name = name.unwrap_or_else(|| before.name.clone());
+            let ticker_symbol = ticker_symbol.unwrap_or_else(|| before.ticker_symbol.clone());
+            let isin = isin.unwrap_or_else(|| before.isin.clone());
+            let updated =
+                AssetMasterRepository::update(conn, id, &name, ticker_symbol, isin, before.version)
+                    .await?;
+            AuditService::updated(
+                conn,
+                None,
+                AuditEntity::AssetMaster,
+                id,
+                Some(&before),
+                &updated,
+            )
+            .await?;
+            Ok(updated)
+        })
+        .await
     }
 
+    /// Deletes the asset with its composition; refused while an account still refers to it
     pub async fn delete(pool: &DbPool, id: &str) -> AppResult<()> {
-        AssetMasterRepository::delete(pool, id).await
+        transaction(pool, async |conn| {
+            let before = AssetMasterRepository::find_by_id(conn, id).await?;
+            if AssetMasterRepository::is_referenced(conn, id).await? {
+                return Err(AppError::Conflict(
+                    "Asset is still used by holdings, groupings or rebalance plans".into(),
+                ));
+            }
+            AssetMasterRepository::delete_composition(conn, id).await?;
+            AssetMasterRepository::delete(conn, id).await?;
+            AuditService::deleted(conn, None, AuditEntity::AssetMaster, id, before.as_ref()).await
+        })
+        .await
+    }
+
+    pub async fn get_composition(pool: &DbPool, id: &str) -> AppResult<Vec<AssetComposition>> {
+        let mut conn = pool.acquire().await?;
+        AssetMasterRepository::find_by_id(&mut conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Asset not found".into()))?;
+        AssetMasterRepository::find_composition(&mut conn, id).await
+    }
+
+    /// `items` is a list of (dimension, label, weight)
+    pub async fn replace_composition(
+        pool: &DbPool,
+        id: &str,
+        items: Vec<(CompositionDimension, String, Decimal)>,
+    ) -> AppResult<Vec<AssetComposition>> {
+        transaction(pool, async |conn| {
+            AssetMasterRepository::find_by_id(conn, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Asset not found".into()))?;
+            let before = AssetMasterRepository::find_composition(conn, id).await?;
+            AssetMasterRepository::delete_composition(conn, id).await?;
+            for (dimension, label, weight) in items {
+                AssetMasterRepository::create_composition_item(
+                    conn,
+                    &generate_id(),
+                    id,
+                    dimension,
+                    label.trim().to_string(),
+                    weight,
+                )
+                .await?;
+            }
+            let after = AssetMasterRepository::find_composition(conn, id).await?;
+            AuditService::updated(
+                conn,
+                None,
+                AuditEntity::AssetComposition,
+                id,
+                Some(&before),
+                &after,
+            )
+            .await?;
+            Ok(after)
+        })
+        .await
     }
 }
@@ -0,0 +1,38 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+
+use crate::core::database::DbPool;
+use crate::domains::health::model::{Liveness, Readiness};
+use crate::domains::health::service::HealthService;
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    responses(
+        (status = 200, description = "The process is up", body = Liveness)
+    ),
+    tag = "health"
+)]
+pub async fn liveness() -> impl IntoResponse {
+    Json(Liveness {
+        status: "ok".to_string(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = 200, description = "Ready to serve traffic", body = Readiness),
+        (status = 503, description = "Database unreachable, migrations pending or in maintenance mode", body = Readiness)
+    ),
+    tag = "health"
+)]
+pub async fn readiness(State(pool): State<DbPool>) -> impl IntoResponse {
+    let readiness = HealthService::readiness(&pool).await;
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
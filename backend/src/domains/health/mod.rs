@@ -0,0 +1,15 @@
+pub mod handler;
+pub mod model;
+pub mod repository;
+pub mod service;
+
+use crate::core::database::DbPool;
+
+use axum::{Router, routing::get};
+
+pub fn health_routes(pool: DbPool) -> Router {
+    Router::new()
+        .route("/healthz", get(handler::liveness))
+        .route("/readyz", get(handler::readiness))
+        .with_state(pool)
+}
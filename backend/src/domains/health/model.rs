@@ -0,0 +1,17 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Liveness {
+    pub status: String,
+}
+
+/// Whether the instance can serve traffic, with the result of each check
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Readiness {
+    pub ready: bool,
+    /// A connection could be acquired and answered a query
+    pub database: bool,
+    /// Versions of migrations shipped with this build that the database lacks
+    pub pending_migrations: Vec<i64>,
+    pub maintenance: bool,
+}
@@ -0,0 +1,30 @@
+use sqlx::query_scalar;
+
+use crate::core::database::DbConn;
+use crate::core::error::AppResult;
+
+pub struct HealthRepository;
+
+impl HealthRepository {
+    pub async fn ping(conn: &mut DbConn) -> AppResult<()> {
+        sqlx::query("SELECT 1").execute(&mut *conn).await?;
+        Ok(())
+    }
+
+    /// Versions recorded by `sqlx migrate`; empty when migrations were never run
+    pub async fn applied_migrations(conn: &mut DbConn) -> AppResult<Vec<i64>> {
+        let table: Option<String> = query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        if table.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let versions = query_scalar("SELECT version FROM _sqlx_migrations WHERE success = 1")
+            .fetch_all(&mut *conn)
+            .await?;
+        Ok(versions)
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use sqlx::migrate::Migrator;
+
+use crate::core::database::DbPool;
+use crate::core::error::AppResult;
+use crate::core::maintenance;
+use crate::domains::health::model::Readiness;
+use crate::domains::health::repository::HealthRepository;
+
+/// Migrations compiled into this build
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// A probe fails instead of waiting out the pool's acquire timeout
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct HealthService;
+
+impl HealthService {
+    pub async fn readiness(pool: &DbPool) -> Readiness {
+        let maintenance = maintenance::is_enabled();
+        let applied =
+            match tokio::time::timeout(DATABASE_CHECK_TIMEOUT, Self::check_database(pool)).await {
+                Ok(Ok(applied)) => Some(applied),
+                Ok(Err(err)) => {
+                    tracing::warn!(error = ?err, "readiness database check failed");
+                    None
+                }
+                Err(_) => {
+                    tracing::warn!("readiness database check timed out");
+                    None
+                }
+            };
+
+        let pending_migrations: Vec<i64> = match &applied {
+            Some(applied) => MIGRATOR
+                .iter()
+                .map(|migration| migration.version)
+                .filter(|version| !applied.contains(version))
+                .collect(),
+            None => Vec::new(),
+        };
+        let database = applied.is_some();
+
+        Readiness {
+            ready: database && pending_migrations.is_empty() && !maintenance,
+            database,
+            pending_migrations,
+            maintenance,
+        }
+    }
+
+    async fn check_database(pool: &DbPool) -> AppResult<Vec<i64>> {
+        let mut conn = pool.acquire().await?;
+        HealthRepository::ping(&mut conn).await?;
+        HealthRepository::applied_migrations(&mut conn).await
+    }
+}
@@ -0,0 +1,66 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::IntoResponse,
+};
+use validator::Validate;
+
+use crate::core::database::DbPool;
+use crate::core::error::AppResult;
+use crate::domains::imports::model::{Broker, ImportPreview};
+use crate::domains::imports::schema::ApplyImportRequest;
+use crate::domains::imports::service::ImportService;
+use crate::domains::portfolio::model::HoldingsDiff;
+use crate::middleware::extractor::{JsonBody, get_account_id};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/imports/{broker}/preview",
+    params(
+        ("broker" = Broker, Path, description = "Statement format")
+    ),
+    request_body(content = String, content_type = "text/csv", description = "Holdings CSV export (Shift_JIS or UTF-8)"),
+    responses(
+        (status = 200, description = "Parsed lines matched against the catalog", body = ImportPreview),
+        (status = 422, description = "Unrecognized file format")
+    ),
+    tag = "imports"
+)]
+pub async fn preview_import(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(broker): Path<Broker>,
+    body: Bytes,
+) -> AppResult<impl IntoResponse> {
+    get_account_id(&headers)?;
+    let preview = ImportService::preview(&pool, broker, &body).await?;
+    Ok(Json(preview))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/imports/apply",
+    request_body = ApplyImportRequest,
+    responses(
+        (status = 200, description = "Holdings updated from the reviewed preview", body = HoldingsDiff),
+        (status = 400, description = "Bad request")
+    ),
+    tag = "imports"
+)]
+pub async fn apply_import(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    JsonBody(payload): JsonBody<ApplyImportRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    payload.validate()?;
+    let lines = payload
+        .lines
+        .into_iter()
+        .map(|line| (line.asset_master_id, line.current_amount))
+        .collect();
+    let diff = ImportService::apply(&pool, &account_id, lines, payload.delete_missing).await?;
+    Ok(Json(diff))
+}
@@ -0,0 +1,16 @@
+pub mod handler;
+pub mod model;
+pub mod parser;
+pub mod schema;
+pub mod service;
+
+use crate::core::database::DbPool;
+
+use axum::{Router, routing::post};
+
+pub fn import_routes(pool: DbPool) -> Router {
+    Router::new()
+        .route("/{broker}/preview", post(handler::preview_import))
+        .route("/apply", post(handler::apply_import))
+        .with_state(pool)
+}
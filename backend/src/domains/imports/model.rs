@@ -0,0 +1,42 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Broker {
+    Sbi,
+    Rakuten,
+    Monex,
+}
+
+/// A holding as read from a statement, before matching against the catalog
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct StatementLine {
+    /// 1-based line number in the file
+    pub line_no: usize,
+    pub name: String,
+    pub ticker_symbol: Option<String>,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CatalogMatch {
+    pub asset_master_id: String,
+    pub asset_name: String,
+    /// 1.0 for an exact ticker or name match, lower for fuzzy name matches
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ImportPreviewLine {
+    #[serde(flatten)]
+    pub line: StatementLine,
+    pub matched: Option<CatalogMatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ImportPreview {
+    pub broker: Broker,
+    pub lines: Vec<ImportPreviewLine>,
+    pub unmatched: usize,
+}
@@ -0,0 +1,280 @@
+pub mod monex;
+pub mod rakuten;
+pub mod sbi;
+
+use std::str::FromStr;
+
+use csv::ReaderBuilder;
+use rust_decimal::Decimal;
+
+use crate::domains::imports::model::{Broker, StatementLine};
+
+/// Footer rows that close a holdings table
+const TOTAL_LABELS: &[&str] = &["合計", "総合計", "小計", "評価額合計"];
+
+/// Parses one broker's holdings CSV export. Implement this to support a new format.
+pub trait StatementParser: Send + Sync {
+    /// Candidate headers of each column, in order of preference
+    fn columns(&self) -> ColumnMap;
+
+    fn parse(&self, text: &str) -> Result<Vec<StatementLine>, String> {
+        parse_table(text, &self.columns())
+    }
+}
+
+pub struct ColumnMap {
+    pub name: &'static [&'static str],
+    pub ticker_symbol: &'static [&'static str],
+    pub amount: &'static [&'static str],
+}
+
+pub fn parser_for(broker: Broker) -> Box<dyn StatementParser> {
+    match broker {
+        Broker::Sbi => Box::new(sbi::SbiParser),
+        Broker::Rakuten => Box::new(rakuten::RakutenParser),
+        Broker::Monex => Box::new(monex::MonexParser),
+    }
+}
+
+/// Broker exports are Shift_JIS (CP932); UTF-8 files, with or without BOM, are accepted too
+pub fn decode_statement(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::SHIFT_JIS.decode(bytes).0.into_owned(),
+    }
+}
+
+/// Reads every holdings table in the export. Each table starts at a header row (exports
+/// carry a preamble above it, and SBI repeats the header per account section such as
+/// 特定預り / NISA預り) and ends at a total row or a row with a blank amount cell, after
+/// which the next header is looked for.
+fn parse_table(text: &str, columns: &ColumnMap) -> Result<Vec<StatementLine>, String> {
+    // The reader reports CRLF lines one short, so positions are counted on LF text
+    let text = text.replace("\r\n", "\n");
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut header: Option<(usize, Option<usize>, usize)> = None;
+    let mut header_found = false;
+    let mut in_section = false;
+    let mut lines = Vec::new();
+
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| format!("Malformed CSV: {}", e))?;
+        let cells: Vec<&str> = record.iter().map(str::trim).collect();
+
+        if let Some(found) = find_header(&cells, columns) {
+            header = Some(found);
+            header_found = true;
+            in_section = false;
+            continue;
+        }
+        let Some((name_col, ticker_col, amount_col)) = header else {
+            continue;
+        };
+
+        let name = cells.get(name_col).copied().unwrap_or_default();
+        if is_total_row(&cells) {
+            header = None;
+            continue;
+        }
+        let Some(raw_amount) = cells.get(amount_col).filter(|raw| !raw.is_empty()) else {
+            // Blank lines and section titles have no amount cell
+            if in_section {
+                header = None;
+            }
+            continue;
+        };
+        // "-" marks a position without a value
+        let Some(amount) = parse_amount(raw_amount) else {
+            continue;
+        };
+        if name.is_empty() {
+            continue;
+        }
+
+        in_section = true;
+        lines.push(StatementLine {
+            // Blank lines are skipped by the reader, so take the line from its position
+            line_no: record
+                .position()
+                .map_or(index + 1, |position| position.line() as usize),
+            name: name.to_string(),
+            ticker_symbol: ticker_col
+                .and_then(|col| cells.get(col))
+                .filter(|ticker| !ticker.is_empty())
+                .map(|ticker| ticker.to_string()),
+            amount,
+        });
+    }
+
+    if !header_found {
+        return Err("Header row not found; is this the right broker format?".into());
+    }
+
+    Ok(lines)
+}
+
+/// Total rows put their label in the first non-empty cell, e.g. "合計" or
+/// "投資信託（金額/特定預り）合計"
+fn is_total_row(cells: &[&str]) -> bool {
+    cells
+        .iter()
+        .find(|cell| !cell.is_empty())
+        .is_some_and(|label| TOTAL_LABELS.iter().any(|total| label.ends_with(total)))
+}
+
+fn find_header(cells: &[&str], columns: &ColumnMap) -> Option<(usize, Option<usize>, usize)> {
+    let position = |candidates: &[&str]| {
+        candidates
+            .iter()
+            .find_map(|candidate| cells.iter().position(|cell| cell == candidate))
+    };
+
+    Some((
+        position(columns.name)?,
+        position(columns.ticker_symbol),
+        position(columns.amount)?,
+    ))
+}
+
+/// Accepts amounts such as "1,234,567", "1234567円" or "+1,234.5"
+fn parse_amount(raw: &str) -> Option<Decimal> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|c| !matches!(c, ',' | '円' | '+' | ' ' | '"'))
+        .collect();
+    if cleaned.is_empty() || cleaned == "-" {
+        return None;
+    }
+    Decimal::from_str(&cleaned).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::{decode_statement, parser_for};
+    use crate::domains::imports::model::Broker;
+
+    /// (line_no, name, ticker_symbol, amount) of each line read from a fixture export
+    fn parse_fixture(broker: Broker, file: &str) -> Vec<(usize, String, Option<String>, Decimal)> {
+        let path = format!(
+            "{}/tests/fixtures/statements/{file}",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let bytes = std::fs::read(path).expect("fixture is readable");
+        parser_for(broker)
+            .parse(&decode_statement(&bytes))
+            .expect("fixture parses")
+            .into_iter()
+            .map(|line| (line.line_no, line.name, line.ticker_symbol, line.amount))
+            .collect()
+    }
+
+    #[test]
+    fn sbi_reads_every_account_section() {
+        assert_eq!(
+            parse_fixture(Broker::Sbi, "sbi.csv"),
+            vec![
+                (
+                    6,
+                    "トヨタ自動車".into(),
+                    Some("7203".into()),
+                    Decimal::from(298_000),
+                ),
+                (
+                    7,
+                    "三菱ＵＦＪフィナンシャル・グループ".into(),
+                    Some("8306".into()),
+                    Decimal::from(495_000),
+                ),
+                (
+                    12,
+                    "eMAXIS Slim 全世界株式（オール・カントリー）".into(),
+                    None,
+                    Decimal::from(3_000_000),
+                ),
+                (
+                    13,
+                    "ｅＭＡＸＩＳ　Ｓｌｉｍ　米国株式（Ｓ＆Ｐ５００）".into(),
+                    None,
+                    Decimal::from(1_550_000),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rakuten_stops_at_the_total_row() {
+        assert_eq!(
+            parse_fixture(Broker::Rakuten, "rakuten.csv"),
+            vec![
+                (
+                    4,
+                    "日本電信電話".into(),
+                    Some("9432".into()),
+                    Decimal::from(168_000),
+                ),
+                (
+                    5,
+                    "バンガード トータル ストック マーケット ETF".into(),
+                    Some("VTI".into()),
+                    Decimal::from(902_410),
+                ),
+                (
+                    6,
+                    "楽天・全米株式インデックス・ファンド".into(),
+                    None,
+                    Decimal::from(1_195_800),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn monex_skips_positions_without_a_value() {
+        assert_eq!(
+            parse_fixture(Broker::Monex, "monex.csv"),
+            vec![
+                (
+                    4,
+                    "MAXIS米国株式(S&P500)上場投信".into(),
+                    Some("2558".into()),
+                    Decimal::from(1_172_500),
+                ),
+                (
+                    6,
+                    "ニッセイ外国株式インデックスファンド".into(),
+                    None,
+                    Decimal::from(710_000),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_shift_jis_and_utf8_with_bom() {
+        let text = "銘柄,時価評価額[円]\r\nｅＭＡＸＩＳ　Ｓｌｉｍ,\"1,000\"\r\n";
+        let (shift_jis, _, unmappable) = encoding_rs::SHIFT_JIS.encode(text);
+        assert!(!unmappable);
+
+        assert_eq!(decode_statement(&shift_jis), text);
+        assert_eq!(decode_statement(format!("\u{FEFF}{text}").as_bytes()), text);
+    }
+
+    #[test]
+    fn rejects_a_file_without_the_brokers_header() {
+        // An SBI export uploaded as Rakuten, and a header with the amount column renamed
+        for text in [
+            "銘柄コード,銘柄名称,評価額\n7203,トヨタ自動車,298000\n",
+            "銘柄,数量\n日本電信電話,1000\n",
+        ] {
+            let result = parser_for(Broker::Rakuten).parse(text);
+            assert!(result.is_err(), "{text:?} parsed as {result:?}");
+        }
+    }
+}
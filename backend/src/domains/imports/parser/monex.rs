@@ -0,0 +1,14 @@
+use crate::domains::imports::parser::{ColumnMap, StatementParser};
+
+/// マネックス証券「保有残高」CSV
+pub struct MonexParser;
+
+impl StatementParser for MonexParser {
+    fn columns(&self) -> ColumnMap {
+        ColumnMap {
+            name: &["銘柄名", "ファンド名"],
+            ticker_symbol: &["銘柄コード"],
+            amount: &["評価金額(円)", "評価額(円)", "評価金額", "評価額"],
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use crate::domains::imports::parser::{ColumnMap, StatementParser};
+
+/// 楽天証券「保有商品一覧」CSV
+pub struct RakutenParser;
+
+impl StatementParser for RakutenParser {
+    fn columns(&self) -> ColumnMap {
+        ColumnMap {
+            name: &["銘柄", "銘柄名"],
+            ticker_symbol: &["銘柄コード・ティッカー", "銘柄コード"],
+            amount: &["時価評価額[円]", "評価額[円]", "時価評価額"],
+        }
+    }
+}
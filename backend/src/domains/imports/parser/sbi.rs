@@ -0,0 +1,14 @@
+use crate::domains::imports::parser::{ColumnMap, StatementParser};
+
+/// SBI証券「保有証券一覧」CSV
+pub struct SbiParser;
+
+impl StatementParser for SbiParser {
+    fn columns(&self) -> ColumnMap {
+        ColumnMap {
+            name: &["ファンド名", "銘柄名称", "銘柄"],
+            ticker_symbol: &["銘柄コード", "コード"],
+            amount: &["評価額", "時価評価額"],
+        }
+    }
+}
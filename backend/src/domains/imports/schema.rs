@@ -0,0 +1,28 @@
+use crate::shared::validation::{validate_holding_amount, validate_non_blank};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct ApplyImportLine {
+    #[validate(custom(
+        function = "validate_non_blank",
+        message = "Asset Master ID cannot be empty or blank"
+    ))]
+    pub asset_master_id: String,
+    #[validate(custom(
+        function = "validate_holding_amount",
+        message = "Amount must be zero or positive with at most 4 decimal places"
+    ))]
+    pub current_amount: Decimal,
+}
+
+/// Reviewed preview lines to write to the account's holdings
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct ApplyImportRequest {
+    #[validate(nested)]
+    pub lines: Vec<ApplyImportLine>,
+    /// Delete holdings that are not in the statement
+    #[serde(default)]
+    pub delete_missing: bool,
+}
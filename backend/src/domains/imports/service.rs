@@ -0,0 +1,164 @@
+use unicode_normalization::UnicodeNormalization;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::core::database::DbPool;
+use crate::core::error::{AppError, AppResult};
+use crate::domains::catalog::model::AssetMaster;
+use crate::domains::catalog::repository::AssetMasterRepository;
+use crate::domains::imports::model::{
+    Broker, CatalogMatch, ImportPreview, ImportPreviewLine, StatementLine,
+};
+use crate::domains::imports::parser::{decode_statement, parser_for};
+use crate::domains::portfolio::model::HoldingsDiff;
+use crate::domains::portfolio::schema::BatchHoldingEntry;
+use crate::domains::portfolio::service::AssetService;
+use rust_decimal::Decimal;
+
+/// Fuzzy name matches below this similarity are left unmatched for the user to resolve
+const MIN_MATCH_SCORE: f64 = 0.8;
+
+pub struct ImportService;
+
+impl ImportService {
+    /// Parses a statement and matches each line to the catalog without writing anything
+    pub async fn preview(pool: &DbPool, broker: Broker, file: &[u8]) -> AppResult<ImportPreview> {
+        let text = decode_statement(file);
+        let lines = parser_for(broker).parse(&text).map_err(file_error)?;
+        let catalog = AssetMasterRepository::find_all(&mut *pool.acquire().await?).await?;
+
+        let lines: Vec<ImportPreviewLine> = lines
+            .into_iter()
+            .map(|line| {
+                let matched = match_catalog(&line, &catalog);
+                ImportPreviewLine { line, matched }
+            })
+            .collect();
+        let unmatched = lines.iter().filter(|line| line.matched.is_none()).count();
+
+        Ok(ImportPreview {
+            broker,
+            lines,
+            unmatched,
+        })
+    }
+
+    /// `lines` is the reviewed list of (asset_master_id, amount)
+    pub async fn apply(
+        pool: &DbPool,
+        account_id: &str,
+        lines: Vec<(String, Decimal)>,
+        delete_missing: bool,
+    ) -> AppResult<HoldingsDiff> {
+        let entries = lines
+            .into_iter()
+            .map(|(asset_master_id, current_amount)| BatchHoldingEntry {
+                asset_master_id: Some(asset_master_id),
+                ticker_symbol: None,
+                current_amount,
+            })
+            .collect();
+        AssetService::sync(pool, account_id, entries, delete_missing, false).await
+    }
+}
+
+/// Ticker first, then exact normalized name, then the closest name by edit distance
+fn match_catalog(line: &StatementLine, catalog: &[AssetMaster]) -> Option<CatalogMatch> {
+    let to_match = |asset: &AssetMaster, score: f64| CatalogMatch {
+        asset_master_id: asset.id.clone(),
+        asset_name: asset.name.clone(),
+        score,
+    };
+
+    if let Some(ticker) = &line.ticker_symbol {
+        let by_ticker = catalog.iter().find(|asset| {
+            asset
+                .ticker_symbol
+                .as_deref()
+                .is_some_and(|symbol| symbol.eq_ignore_ascii_case(ticker))
+        });
+        if let Some(asset) = by_ticker {
+            return Some(to_match(asset, 1.0));
+        }
+    }
+
+    let name = normalize_name(&line.name);
+    catalog
+        .iter()
+        .map(|asset| {
+            let score = strsim::normalized_levenshtein(&name, &normalize_name(&asset.name));
+            (asset, score)
+        })
+        .filter(|(_, score)| *score >= MIN_MATCH_SCORE)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(asset, score)| to_match(asset, score))
+}
+
+/// NFKC folds full-width characters; spaces and punctuation such as "・" or "（）" are dropped
+fn normalize_name(name: &str) -> String {
+    name.nfkc()
+        .flat_map(char::to_lowercase)
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+fn file_error(message: String) -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        "file",
+        ValidationError::new("statement_format").with_message(message.into()),
+    );
+    AppError::Unprocessable(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImportService;
+    use crate::core::database::test_pool;
+    use crate::core::error::AppError;
+    use crate::domains::imports::model::Broker;
+
+    #[tokio::test]
+    async fn preview_matches_lines_by_ticker_then_name() {
+        let pool = test_pool().await;
+        sqlx::query(
+            "INSERT INTO asset_master (id, name, ticker_symbol) VALUES \
+             ('ntt', 'NTT', '9432'), \
+             ('vti', 'Vanguard Total Stock Market ETF', 'vti'), \
+             ('fund', '楽天 全米株式インデックス ファンド', NULL)",
+        )
+        .execute(&pool)
+        .await
+        .expect("catalog is seeded");
+        let text = "銘柄コード・ティッカー,銘柄,時価評価額[円]\n\
+                    9432,日本電信電話,168000\n\
+                    VTI,バンガード トータル ストック マーケット ETF,902410\n\
+                    ,楽天・全米株式インデックス・ファンド,1195800\n\
+                    ,ひふみプラス,50000\n";
+        let (file, _, _) = encoding_rs::SHIFT_JIS.encode(text);
+
+        let preview = ImportService::preview(&pool, Broker::Rakuten, &file)
+            .await
+            .expect("statement is previewed");
+
+        let matched: Vec<Option<&str>> = preview
+            .lines
+            .iter()
+            .map(|line| line.matched.as_ref().map(|m| m.asset_master_id.as_str()))
+            .collect();
+        assert_eq!(matched, [Some("ntt"), Some("vti"), Some("fund"), None]);
+        assert_eq!(preview.unmatched, 1);
+    }
+
+    #[tokio::test]
+    async fn preview_rejects_another_brokers_file() {
+        let pool = test_pool().await;
+        let file = "銘柄コード,銘柄名称,評価額\n7203,トヨタ自動車,298000\n";
+
+        let result = ImportService::preview(&pool, Broker::Rakuten, file.as_bytes()).await;
+
+        let Err(AppError::Unprocessable(errors)) = result else {
+            panic!("expected the file to be refused");
+        };
+        assert!(errors.field_errors().contains_key("file"));
+    }
+}
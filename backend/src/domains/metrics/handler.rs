@@ -0,0 +1,18 @@
+use axum::{extract::State, http::header::CONTENT_TYPE, response::IntoResponse};
+
+use crate::core::database::DbPool;
+use crate::core::error::AppResult;
+use crate::domains::metrics::service::MetricsService;
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text exposition format", body = String, content_type = "text/plain")
+    ),
+    tag = "metrics"
+)]
+pub async fn scrape(State(pool): State<DbPool>) -> AppResult<impl IntoResponse> {
+    let body = MetricsService::scrape(&pool).await?;
+    Ok(([(CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
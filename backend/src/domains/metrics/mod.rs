@@ -0,0 +1,13 @@
+pub mod handler;
+pub mod repository;
+pub mod service;
+
+use crate::core::database::DbPool;
+
+use axum::{Router, routing::get};
+
+pub fn metrics_routes(pool: DbPool) -> Router {
+    Router::new()
+        .route("/metrics", get(handler::scrape))
+        .with_state(pool)
+}
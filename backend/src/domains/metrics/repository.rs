@@ -0,0 +1,29 @@
+use sqlx::query_scalar;
+
+use crate::core::database::DbConn;
+use crate::core::error::AppResult;
+
+pub struct MetricsRepository;
+
+impl MetricsRepository {
+    pub async fn count_active_accounts(conn: &mut DbConn) -> AppResult<i64> {
+        let count = query_scalar("SELECT COUNT(*) FROM accounts WHERE deleted_at IS NULL")
+            .fetch_one(&mut *conn)
+            .await?;
+        Ok(count)
+    }
+
+    pub async fn count_active_holdings(conn: &mut DbConn) -> AppResult<i64> {
+        let count = query_scalar("SELECT COUNT(*) FROM assets WHERE deleted_at IS NULL")
+            .fetch_one(&mut *conn)
+            .await?;
+        Ok(count)
+    }
+
+    pub async fn count_catalog_entries(conn: &mut DbConn) -> AppResult<i64> {
+        let count = query_scalar("SELECT COUNT(*) FROM asset_master")
+            .fetch_one(&mut *conn)
+            .await?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,34 @@
+use ::metrics::gauge;
+
+use crate::core::database::DbPool;
+use crate::core::error::AppResult;
+use crate::core::metrics::render;
+use crate::domains::metrics::repository::MetricsRepository;
+
+pub struct MetricsService;
+
+impl MetricsService {
+    /// Refreshes the gauges sampled at scrape time and renders every metric
+    pub async fn scrape(pool: &DbPool) -> AppResult<String> {
+        Self::record_pool(pool);
+
+        let mut conn = pool.acquire().await?;
+        let accounts = MetricsRepository::count_active_accounts(&mut conn).await?;
+        let holdings = MetricsRepository::count_active_holdings(&mut conn).await?;
+        let catalog = MetricsRepository::count_catalog_entries(&mut conn).await?;
+        drop(conn);
+        gauge!("accounts_active").set(accounts as f64);
+        gauge!("holdings_active").set(holdings as f64);
+        gauge!("catalog_entries").set(catalog as f64);
+
+        Ok(render())
+    }
+
+    fn record_pool(pool: &DbPool) {
+        let size = pool.size();
+        let idle = pool.num_idle() as u32;
+        gauge!("db_pool_connections", "state" => "idle").set(idle);
+        gauge!("db_pool_connections", "state" => "in_use").set(size.saturating_sub(idle));
+        gauge!("db_pool_max_connections").set(pool.options().get_max_connections());
+    }
+}
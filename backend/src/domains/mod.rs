@@ -1,3 +1,9 @@
 pub mod accounts;
+pub mod admin;
+pub mod audit;
 pub mod catalog;
+pub mod health;
+pub mod imports;
+pub mod metrics;
 pub mod portfolio;
+pub mod rebalance;
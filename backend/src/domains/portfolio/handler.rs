@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
@@ -8,16 +8,22 @@ use validator::Validate;
 
 use crate::core::database::DbPool;
 use crate::core::error::{AppError, AppResult};
-use crate::domains::portfolio::model::{Asset, AssetCategory, UserAssetGrouping};
+use crate::domains::portfolio::model::{
+    Asset, AssetCategory, GroupingSuggestion, HoldingsDiff, LookThroughReport, PortfolioAllocation,
+    UserAssetGrouping,
+};
 use crate::domains::portfolio::schema::{
-    CreateCategoryRequest, CreateGroupingRequest, CreateUserAssetRequest, UpdateCategoryRequest,
-    UpdateGroupingRequest, UpdateUserAssetRequest,
+    BatchUpsertHoldingsRequest, CompositionQuery, CreateCategoryRequest, CreateGroupingRequest,
+    CreateUserAssetRequest, PatchCategoryRequest, PatchGroupingRequest, PatchUserAssetRequest,
+    ReplaceGroupingSplitRequest, UpdateCategoryRequest, UpdateGroupingRequest,
+    UpdateUserAssetRequest,
 };
 use crate::domains::portfolio::service::{
-    AssetCategoryService, AssetGroupingService, AssetService,
+    AllocationService, AssetCategoryService, AssetGroupingService, AssetService, LookThroughService,
 };
 
-use crate::middleware::extractor::get_account_id;
+use crate::middleware::extractor::{JsonBody, get_account_id};
+use crate::shared::etag::{etag, parse_if_match};
 
 // --- Asset Category Handler ---
 
@@ -27,14 +33,15 @@ use crate::middleware::extractor::get_account_id;
     request_body = CreateCategoryRequest,
     responses(
         (status = 201, description = "Category created successfully", body = AssetCategory),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified")
     ),
     tag = "portfolio"
 )]
 pub async fn create_category(
     State(pool): State<DbPool>,
     headers: HeaderMap,
-    Json(payload): Json<CreateCategoryRequest>,
+    JsonBody(payload): JsonBody<CreateCategoryRequest>,
 ) -> AppResult<impl IntoResponse> {
     let account_id = get_account_id(&headers)?;
     payload.validate()?;
@@ -48,7 +55,8 @@ pub async fn create_category(
     get,
     path = "/api/v1/portfolio/categories",
     responses(
-        (status = 200, description = "List all categories", body = [AssetCategory])
+        (status = 200, description = "List all categories", body = [AssetCategory]),
+        (status = 401, description = "Caller not identified")
     ),
     tag = "portfolio"
 )]
@@ -68,7 +76,8 @@ pub async fn list_categories(
         ("id" = String, Path, description = "Category ID")
     ),
     responses(
-        (status = 200, description = "Category found", body = AssetCategory),
+        (status = 200, description = "Category found", body = AssetCategory, headers(("ETag" = String, description = "Current version of the category"))),
+        (status = 401, description = "Caller not identified"),
         (status = 404, description = "Category not found")
     ),
     tag = "portfolio"
@@ -80,19 +89,22 @@ pub async fn get_category(
     let category = AssetCategoryService::get_by_id(&pool, &id)
         .await?
         .ok_or_else(|| AppError::NotFound("Category not found".into()))?;
-    Ok(Json(category))
+    Ok((etag(category.version), Json(category)))
 }
 
 #[utoipa::path(
     put,
     path = "/api/v1/portfolio/categories/{id}",
     params(
-        ("id" = String, Path, description = "Category ID")
+        ("id" = String, Path, description = "Category ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
     ),
     request_body = UpdateCategoryRequest,
     responses(
-        (status = 200, description = "Category updated successfully", body = AssetCategory),
-        (status = 404, description = "Category not found")
+        (status = 200, description = "Category updated successfully", body = AssetCategory, headers(("ETag" = String, description = "Current version of the category"))),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "Category not found"),
+        (status = 412, description = "The category changed since the If-Match version; the body holds its current state")
     ),
     tag = "portfolio"
 )]
@@ -100,14 +112,59 @@ pub async fn update_category(
     State(pool): State<DbPool>,
     headers: HeaderMap,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateCategoryRequest>,
+    JsonBody(payload): JsonBody<UpdateCategoryRequest>,
 ) -> AppResult<impl IntoResponse> {
     let account_id = get_account_id(&headers)?;
+    let expected_version = parse_if_match(&headers)?;
     payload.validate()?;
-    let category =
-        AssetCategoryService::update(&pool, &id, &account_id, payload.name, payload.target_ratio)
-            .await?;
-    Ok(Json(category))
+    let category = AssetCategoryService::update(
+        &pool,
+        &id,
+        &account_id,
+        Some(payload.name),
+        Some(payload.target_ratio),
+        expected_version,
+    )
+    .await?;
+    Ok((etag(category.version), Json(category)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/portfolio/categories/{id}",
+    params(
+        ("id" = String, Path, description = "Category ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
+    ),
+    request_body = PatchCategoryRequest,
+    responses(
+        (status = 200, description = "Present fields of the category updated", body = AssetCategory, headers(("ETag" = String, description = "Current version of the category"))),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "Category not found"),
+        (status = 412, description = "The category changed since the If-Match version; the body holds its current state")
+    ),
+    tag = "portfolio"
+)]
+pub async fn patch_category(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<PatchCategoryRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let expected_version = parse_if_match(&headers)?;
+    payload.validate()?;
+    let category = AssetCategoryService::update(
+        &pool,
+        &id,
+        &account_id,
+        payload.name,
+        payload.target_ratio,
+        expected_version,
+    )
+    .await?;
+    Ok((etag(category.version), Json(category)))
 }
 
 #[utoipa::path(
@@ -117,7 +174,8 @@ pub async fn update_category(
         ("id" = String, Path, description = "Category ID")
     ),
     responses(
-        (status = 204, description = "Category deleted successfully"),
+        (status = 204, description = "Category and its groupings moved to trash"),
+        (status = 401, description = "Caller not identified"),
         (status = 404, description = "Category not found")
     ),
     tag = "portfolio"
@@ -132,6 +190,29 @@ pub async fn delete_category(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/portfolio/categories/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Category ID")
+    ),
+    responses(
+        (status = 200, description = "Category restored", body = AssetCategory),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "No deleted category with this ID")
+    ),
+    tag = "portfolio"
+)]
+pub async fn restore_category(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let restored = AssetCategoryService::restore(&pool, &id, &account_id).await?;
+    Ok(Json(restored))
+}
+
 // --- Asset Grouping Handler ---
 
 #[utoipa::path(
@@ -140,14 +221,16 @@ pub async fn delete_category(
     request_body = CreateGroupingRequest,
     responses(
         (status = 201, description = "Grouping created successfully", body = UserAssetGrouping),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 422, description = "The asset or category does not exist for this account")
     ),
     tag = "portfolio"
 )]
 pub async fn create_grouping(
     State(pool): State<DbPool>,
     headers: HeaderMap,
-    Json(payload): Json<CreateGroupingRequest>,
+    JsonBody(payload): JsonBody<CreateGroupingRequest>,
 ) -> AppResult<impl IntoResponse> {
     let account_id = get_account_id(&headers)?;
     payload.validate()?;
@@ -156,6 +239,7 @@ pub async fn create_grouping(
         &account_id,
         payload.asset_master_id,
         payload.category_id,
+        payload.weight,
     )
     .await?;
     Ok((StatusCode::CREATED, Json(grouping)))
@@ -165,7 +249,8 @@ pub async fn create_grouping(
     get,
     path = "/api/v1/portfolio/groupings",
     responses(
-        (status = 200, description = "List all groupings", body = [UserAssetGrouping])
+        (status = 200, description = "List all groupings", body = [UserAssetGrouping]),
+        (status = 401, description = "Caller not identified")
     ),
     tag = "portfolio"
 )]
@@ -185,7 +270,8 @@ pub async fn list_groupings(
         ("id" = String, Path, description = "Grouping ID")
     ),
     responses(
-        (status = 200, description = "Grouping found", body = UserAssetGrouping),
+        (status = 200, description = "Grouping found", body = UserAssetGrouping, headers(("ETag" = String, description = "Current version of the grouping"))),
+        (status = 401, description = "Caller not identified"),
         (status = 404, description = "Grouping not found")
     ),
     tag = "portfolio"
@@ -197,19 +283,23 @@ pub async fn get_grouping(
     let grouping = AssetGroupingService::get_by_id(&pool, &id)
         .await?
         .ok_or_else(|| AppError::NotFound("Grouping not found".into()))?;
-    Ok(Json(grouping))
+    Ok((etag(grouping.version), Json(grouping)))
 }
 
 #[utoipa::path(
     put,
     path = "/api/v1/portfolio/groupings/{id}",
     params(
-        ("id" = String, Path, description = "Grouping ID")
+        ("id" = String, Path, description = "Grouping ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
     ),
     request_body = UpdateGroupingRequest,
     responses(
-        (status = 200, description = "Grouping updated successfully", body = UserAssetGrouping),
-        (status = 404, description = "Grouping not found")
+        (status = 200, description = "Grouping updated successfully", body = UserAssetGrouping, headers(("ETag" = String, description = "Current version of the grouping"))),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "Grouping not found"),
+        (status = 412, description = "The grouping changed since the If-Match version; the body holds its current state"),
+        (status = 422, description = "The category does not exist for this account")
     ),
     tag = "portfolio"
 )]
@@ -217,13 +307,60 @@ pub async fn update_grouping(
     State(pool): State<DbPool>,
     headers: HeaderMap,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateGroupingRequest>,
+    JsonBody(payload): JsonBody<UpdateGroupingRequest>,
 ) -> AppResult<impl IntoResponse> {
     let account_id = get_account_id(&headers)?;
+    let expected_version = parse_if_match(&headers)?;
     payload.validate()?;
-    let grouping =
-        AssetGroupingService::update(&pool, &id, &account_id, payload.category_id).await?;
-    Ok(Json(grouping))
+    let grouping = AssetGroupingService::update(
+        &pool,
+        &id,
+        &account_id,
+        Some(payload.category_id),
+        Some(payload.weight),
+        expected_version,
+    )
+    .await?;
+    Ok((etag(grouping.version), Json(grouping)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/portfolio/groupings/{id}",
+    params(
+        ("id" = String, Path, description = "Grouping ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
+    ),
+    request_body = PatchGroupingRequest,
+    responses(
+        (status = 200, description = "Present fields of the grouping updated", body = UserAssetGrouping, headers(("ETag" = String, description = "Current version of the grouping"))),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "Grouping not found"),
+        (status = 412, description = "The grouping changed since the If-Match version; the body holds its current state"),
+        (status = 422, description = "The category does not exist for this account")
+    ),
+    tag = "portfolio"
+)]
+pub async fn patch_grouping(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<PatchGroupingRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let expected_version = parse_if_match(&headers)?;
+    payload.validate()?;
+    let grouping = AssetGroupingService::update(
+        &pool,
+        &id,
+        &account_id,
+        payload.category_id,
+        payload.weight,
+        expected_version,
+    )
+    .await?;
+    Ok((etag(grouping.version), Json(grouping)))
 }
 
 #[utoipa::path(
@@ -234,6 +371,7 @@ pub async fn update_grouping(
     ),
     responses(
         (status = 204, description = "Grouping deleted successfully"),
+        (status = 401, description = "Caller not identified"),
         (status = 404, description = "Grouping not found")
     ),
     tag = "portfolio"
@@ -248,6 +386,111 @@ pub async fn delete_grouping(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/portfolio/groupings/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Grouping ID")
+    ),
+    responses(
+        (status = 200, description = "Grouping restored", body = UserAssetGrouping),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "No deleted grouping with this ID"),
+        (status = 409, description = "The category is deleted or the assignment exists again")
+    ),
+    tag = "portfolio"
+)]
+pub async fn restore_grouping(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let restored = AssetGroupingService::restore(&pool, &id, &account_id).await?;
+    Ok(Json(restored))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/portfolio/groupings/assets/{asset_master_id}",
+    params(
+        ("asset_master_id" = String, Path, description = "Asset Master ID")
+    ),
+    responses(
+        (status = 200, description = "Weighted groupings of the asset", body = [UserAssetGrouping]),
+        (status = 401, description = "Caller not identified")
+    ),
+    tag = "portfolio"
+)]
+pub async fn get_grouping_split(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(asset_master_id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let groupings = AssetGroupingService::get_split(&pool, &account_id, &asset_master_id).await?;
+    Ok(Json(groupings))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/portfolio/groupings/assets/{asset_master_id}",
+    params(
+        ("asset_master_id" = String, Path, description = "Asset Master ID")
+    ),
+    request_body = ReplaceGroupingSplitRequest,
+    responses(
+        (status = 200, description = "Weighted groupings replaced successfully", body = [UserAssetGrouping]),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 422, description = "The asset or a category does not exist for this account")
+    ),
+    tag = "portfolio"
+)]
+pub async fn replace_grouping_split(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(asset_master_id): Path<String>,
+    JsonBody(payload): JsonBody<ReplaceGroupingSplitRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    payload.validate()?;
+    let splits = payload
+        .splits
+        .into_iter()
+        .map(|split| (split.category_id, split.weight))
+        .collect();
+    let groupings =
+        AssetGroupingService::replace_split(&pool, &account_id, &asset_master_id, splits).await?;
+    Ok(Json(groupings))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/portfolio/groupings/assets/{asset_master_id}/suggestions",
+    params(
+        ("asset_master_id" = String, Path, description = "Asset Master ID"),
+        CompositionQuery
+    ),
+    responses(
+        (status = 200, description = "Weighted categories suggested from the catalog breakdown", body = [GroupingSuggestion]),
+        (status = 401, description = "Caller not identified")
+    ),
+    tag = "portfolio"
+)]
+pub async fn suggest_grouping_split(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(asset_master_id): Path<String>,
+    Query(query): Query<CompositionQuery>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let suggestions =
+        AssetGroupingService::suggest_split(&pool, &account_id, &asset_master_id, query.dimension)
+            .await?;
+    Ok(Json(suggestions))
+}
+
 // --- Asset Handler ---
 
 #[utoipa::path(
@@ -256,14 +499,17 @@ pub async fn delete_grouping(
     request_body = CreateUserAssetRequest,
     responses(
         (status = 201, description = "Asset (holding) created successfully", body = Asset),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 409, description = "The account already holds this asset"),
+        (status = 422, description = "The asset does not exist in the catalog")
     ),
     tag = "portfolio"
 )]
 pub async fn create_user_asset(
     State(pool): State<DbPool>,
     headers: HeaderMap,
-    Json(payload): Json<CreateUserAssetRequest>,
+    JsonBody(payload): JsonBody<CreateUserAssetRequest>,
 ) -> AppResult<impl IntoResponse> {
     let account_id = get_account_id(&headers)?;
     payload.validate()?;
@@ -272,6 +518,7 @@ pub async fn create_user_asset(
         &account_id,
         payload.asset_master_id,
         payload.current_amount,
+        payload.is_liability,
     )
     .await?;
     Ok((StatusCode::CREATED, Json(asset)))
@@ -281,7 +528,8 @@ pub async fn create_user_asset(
     get,
     path = "/api/v1/portfolio/assets",
     responses(
-        (status = 200, description = "List all assets (holdings)", body = [Asset])
+        (status = 200, description = "List all assets (holdings)", body = [Asset]),
+        (status = 401, description = "Caller not identified")
     ),
     tag = "portfolio"
 )]
@@ -301,7 +549,8 @@ pub async fn list_user_assets(
         ("id" = String, Path, description = "Asset holding ID")
     ),
     responses(
-        (status = 200, description = "Asset holding found", body = Asset),
+        (status = 200, description = "Asset holding found", body = Asset, headers(("ETag" = String, description = "Current version of the holding"))),
+        (status = 401, description = "Caller not identified"),
         (status = 404, description = "Asset holding not found")
     ),
     tag = "portfolio"
@@ -313,19 +562,23 @@ pub async fn get_user_asset(
     let asset = AssetService::get_by_id(&pool, &id)
         .await?
         .ok_or_else(|| AppError::NotFound("Asset not found".into()))?;
-    Ok(Json(asset))
+    Ok((etag(asset.version), Json(asset)))
 }
 
 #[utoipa::path(
     put,
     path = "/api/v1/portfolio/assets/{id}",
     params(
-        ("id" = String, Path, description = "Asset holding ID")
+        ("id" = String, Path, description = "Asset holding ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
     ),
     request_body = UpdateUserAssetRequest,
     responses(
-        (status = 200, description = "Asset holding updated successfully", body = Asset),
-        (status = 404, description = "Asset holding not found")
+        (status = 200, description = "Asset holding updated successfully", body = Asset, headers(("ETag" = String, description = "Current version of the holding"))),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "Asset holding not found"),
+        (status = 412, description = "The holding changed since the If-Match version; the body holds its current state")
     ),
     tag = "portfolio"
 )]
@@ -333,12 +586,59 @@ pub async fn update_user_asset(
     State(pool): State<DbPool>,
     headers: HeaderMap,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateUserAssetRequest>,
+    JsonBody(payload): JsonBody<UpdateUserAssetRequest>,
 ) -> AppResult<impl IntoResponse> {
     let account_id = get_account_id(&headers)?;
+    let expected_version = parse_if_match(&headers)?;
     payload.validate()?;
-    let asset = AssetService::update(&pool, &id, &account_id, payload.current_amount).await?;
-    Ok(Json(asset))
+    let asset = AssetService::update(
+        &pool,
+        &id,
+        &account_id,
+        Some(payload.current_amount),
+        Some(payload.is_liability),
+        expected_version,
+    )
+    .await?;
+    Ok((etag(asset.version), Json(asset)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/portfolio/assets/{id}",
+    params(
+        ("id" = String, Path, description = "Asset holding ID"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the version being edited")
+    ),
+    request_body = PatchUserAssetRequest,
+    responses(
+        (status = 200, description = "Present fields of the asset holding updated", body = Asset, headers(("ETag" = String, description = "Current version of the holding"))),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "Asset holding not found"),
+        (status = 412, description = "The holding changed since the If-Match version; the body holds its current state")
+    ),
+    tag = "portfolio"
+)]
+pub async fn patch_user_asset(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    JsonBody(payload): JsonBody<PatchUserAssetRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let expected_version = parse_if_match(&headers)?;
+    payload.validate()?;
+    let asset = AssetService::update(
+        &pool,
+        &id,
+        &account_id,
+        payload.current_amount,
+        payload.is_liability,
+        expected_version,
+    )
+    .await?;
+    Ok((etag(asset.version), Json(asset)))
 }
 
 #[utoipa::path(
@@ -349,6 +649,7 @@ pub async fn update_user_asset(
     ),
     responses(
         (status = 204, description = "Asset holding deleted successfully"),
+        (status = 401, description = "Caller not identified"),
         (status = 404, description = "Asset holding not found")
     ),
     tag = "portfolio"
@@ -362,3 +663,96 @@ pub async fn delete_user_asset(
     AssetService::delete(&pool, &id, &account_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/portfolio/assets/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Asset ID")
+    ),
+    responses(
+        (status = 200, description = "Asset restored", body = Asset),
+        (status = 401, description = "Caller not identified"),
+        (status = 404, description = "No deleted asset with this ID"),
+        (status = 409, description = "The asset is held again")
+    ),
+    tag = "portfolio"
+)]
+pub async fn restore_user_asset(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let restored = AssetService::restore(&pool, &id, &account_id).await?;
+    Ok(Json(restored))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/portfolio/assets:batch",
+    request_body = BatchUpsertHoldingsRequest,
+    responses(
+        (status = 200, description = "Holdings synced (or previewed when dry_run is set)", body = HoldingsDiff),
+        (status = 400, description = "Bad request"),
+        (status = 401, description = "Caller not identified")
+    ),
+    tag = "portfolio"
+)]
+pub async fn batch_upsert_user_assets(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    JsonBody(payload): JsonBody<BatchUpsertHoldingsRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    payload.validate()?;
+    let diff = AssetService::sync(
+        &pool,
+        &account_id,
+        payload.holdings,
+        payload.delete_missing,
+        payload.dry_run,
+    )
+    .await?;
+    Ok(Json(diff))
+}
+
+// --- Allocation Handler ---
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/portfolio/allocation",
+    responses(
+        (status = 200, description = "Current allocation per category", body = PortfolioAllocation),
+        (status = 401, description = "Caller not identified")
+    ),
+    tag = "portfolio"
+)]
+pub async fn get_allocation(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let allocation = AllocationService::calculate(&pool, &account_id).await?;
+    Ok(Json(allocation))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/portfolio/look-through",
+    params(CompositionQuery),
+    responses(
+        (status = 200, description = "Exposure across all funds by catalog breakdown", body = LookThroughReport),
+        (status = 401, description = "Caller not identified")
+    ),
+    tag = "portfolio"
+)]
+pub async fn get_look_through(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Query(query): Query<CompositionQuery>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let report = LookThroughService::calculate(&pool, &account_id, query.dimension).await?;
+    Ok(Json(report))
+}
@@ -8,7 +8,7 @@ use crate::core::database::DbPool;
 
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{get, post, put},
 };
 
 pub fn portfolio_routes(pool: DbPool) -> Router {
@@ -22,8 +22,10 @@ pub fn portfolio_routes(pool: DbPool) -> Router {
             "/categories/{id}",
             get(handler::get_category)
                 .put(handler::update_category)
+                .patch(handler::patch_category)
                 .delete(handler::delete_category),
         )
+        .route("/categories/{id}/restore", post(handler::restore_category))
         // Groupings
         .route(
             "/groupings",
@@ -33,8 +35,18 @@ pub fn portfolio_routes(pool: DbPool) -> Router {
             "/groupings/{id}",
             get(handler::get_grouping)
                 .put(handler::update_grouping)
+                .patch(handler::patch_grouping)
                 .delete(handler::delete_grouping),
         )
+        .route("/groupings/{id}/restore", post(handler::restore_grouping))
+        .route(
+            "/groupings/assets/{asset_master_id}",
+            get(handler::get_grouping_split).put(handler::replace_grouping_split),
+        )
+        .route(
+            "/groupings/assets/{asset_master_id}/suggestions",
+            get(handler::suggest_grouping_split),
+        )
         // Assets
         .route(
             "/assets",
@@ -44,7 +56,13 @@ pub fn portfolio_routes(pool: DbPool) -> Router {
             "/assets/{id}",
             get(handler::get_user_asset)
                 .put(handler::update_user_asset)
+                .patch(handler::patch_user_asset)
                 .delete(handler::delete_user_asset),
         )
+        .route("/assets/{id}/restore", post(handler::restore_user_asset))
+        .route("/assets:batch", put(handler::batch_upsert_user_assets))
+        // Allocation
+        .route("/allocation", get(handler::get_allocation))
+        .route("/look-through", get(handler::get_look_through))
         .with_state(pool)
 }
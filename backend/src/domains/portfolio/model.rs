@@ -1,69 +1,163 @@
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use rust_decimal::prelude::FromPrimitive;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Row, sqlite::SqliteRow};
 
+use crate::domains::catalog::model::CompositionDimension;
+use crate::shared::decimal::decode_decimal;
+
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct AssetCategory {
     pub id: String,
     pub account_id: String,
     pub name: String,
     pub target_ratio: Decimal,
+    /// Bumped on every change; sent as the ETag
+    #[serde(default)]
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl<'r> FromRow<'r, SqliteRow> for AssetCategory {
     fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
-        let target_ratio_f64: f64 = row.try_get("target_ratio")?;
-        let target_ratio = Decimal::from_f64(target_ratio_f64)
-            .ok_or_else(|| sqlx::Error::Decode("Failed to convert f64 to Decimal".into()))?;
-
         Ok(Self {
             id: row.try_get("id")?,
             account_id: row.try_get("account_id")?,
             name: row.try_get("name")?,
-            target_ratio,
+            target_ratio: decode_decimal(row, "target_ratio")?,
+            version: row.try_get("version")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow, utoipa::ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct UserAssetGrouping {
     pub id: String,
     pub account_id: String,
     pub asset_master_id: String,
     pub category_id: String,
+    /// Share of the asset assigned to the category (%)
+    pub weight: Decimal,
+    /// Bumped on every change; sent as the ETag
+    #[serde(default)]
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl<'r> FromRow<'r, SqliteRow> for UserAssetGrouping {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            account_id: row.try_get("account_id")?,
+            asset_master_id: row.try_get("asset_master_id")?,
+            category_id: row.try_get("category_id")?,
+            weight: decode_decimal(row, "weight")?,
+            version: row.try_get("version")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Asset {
     pub id: String,
     pub account_id: String,
     pub asset_master_id: String,
     pub current_amount: Decimal,
+    /// Short position or loan; its amount is owed rather than held
+    #[serde(default)]
+    pub is_liability: bool,
+    /// Bumped on every change; sent as the ETag
+    #[serde(default)]
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl<'r> FromRow<'r, SqliteRow> for Asset {
     fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
-        let current_amount_f64: f64 = row.try_get("current_amount")?;
-        let current_amount = Decimal::from_f64(current_amount_f64)
-            .ok_or_else(|| sqlx::Error::Decode("Failed to convert f64 to Decimal".into()))?;
-
         Ok(Self {
             id: row.try_get("id")?,
             account_id: row.try_get("account_id")?,
             asset_master_id: row.try_get("asset_master_id")?,
-            current_amount,
+            current_amount: decode_decimal(row, "current_amount")?,
+            is_liability: row.try_get("is_liability")?,
+            version: row.try_get("version")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CategoryAllocation {
+    pub category_id: String,
+    pub name: String,
+    pub target_ratio: Decimal,
+    pub current_amount: Decimal,
+    pub current_ratio: Decimal,
+    /// current_ratio - target_ratio (percentage points)
+    pub deviation: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PortfolioAllocation {
+    /// Sum of held assets; ratios are shares of this amount
+    pub total_amount: Decimal,
+    pub categories: Vec<CategoryAllocation>,
+    /// Holdings (or parts of holdings) not assigned to any category
+    pub unallocated_amount: Decimal,
+    /// Sum of holdings marked as liabilities, kept out of the categories
+    pub liability_amount: Decimal,
+    /// total_amount - liability_amount
+    pub net_amount: Decimal,
+}
+
+/// Category suggested for one line of a fund's catalog breakdown
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct GroupingSuggestion {
+    pub label: String,
+    pub weight: Decimal,
+    /// Existing category whose name matches the label, if any
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Exposure {
+    pub label: String,
+    pub amount: Decimal,
+    pub ratio: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LookThroughReport {
+    pub dimension: CompositionDimension,
+    pub total_amount: Decimal,
+    pub exposures: Vec<Exposure>,
+    /// Holdings whose catalog entry has no breakdown for the dimension
+    pub uncovered_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct HoldingChange {
+    pub asset_master_id: String,
+    pub previous_amount: Option<Decimal>,
+    pub current_amount: Option<Decimal>,
+}
+
+/// Result of syncing holdings against a statement
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct HoldingsDiff {
+    pub created: Vec<HoldingChange>,
+    pub updated: Vec<HoldingChange>,
+    pub deleted: Vec<HoldingChange>,
+    pub unchanged: usize,
+    /// false for a dry run
+    pub applied: bool,
+}
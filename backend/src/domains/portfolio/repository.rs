@@ -1,9 +1,10 @@
-use crate::core::database::DbPool;
+use crate::core::database::DbConn;
 use crate::core::error::{AppError, AppResult};
 use crate::domains::portfolio::model::{Asset, AssetCategory, UserAssetGrouping};
+use crate::shared::etag::precondition_failed;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
-use sqlx::query_as;
+use sqlx::{query_as, query_scalar};
 
 // --- Asset Categories Repository ---
 
@@ -11,7 +12,7 @@ pub struct AssetCategoryRepository;
 
 impl AssetCategoryRepository {
     pub async fn create(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         account_id: &str,
         name: String,
@@ -26,88 +27,106 @@ impl AssetCategoryRepository {
         .bind(id)
         .bind(account_id)
         .bind(name)
-        .bind(target_ratio.to_f64().unwrap_or(0.0))
-        .execute(pool)
+        .bind(target_ratio.to_string())
+        .execute(&mut *conn)
         .await?;
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Created category not found".into()))
     }
 
-    pub async fn find_by_account(pool: &DbPool, account_id: &str) -> AppResult<Vec<AssetCategory>> {
+    pub async fn find_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+    ) -> AppResult<Vec<AssetCategory>> {
         let categories = query_as::<_, AssetCategory>(
             r#"
-            SELECT id, account_id, name, target_ratio, created_at, updated_at
+            SELECT id, account_id, name, target_ratio, version, created_at, updated_at
             FROM asset_categories
-            WHERE account_id = ?
+            WHERE account_id = ? AND deleted_at IS NULL
             ORDER BY created_at DESC
             "#,
         )
         .bind(account_id)
-        .fetch_all(pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(categories)
     }
 
-    pub async fn find_by_id(pool: &DbPool, id: &str) -> AppResult<Option<AssetCategory>> {
+    pub async fn find_by_id(conn: &mut DbConn, id: &str) -> AppResult<Option<AssetCategory>> {
         let category = query_as::<_, AssetCategory>(
             r#"
-            SELECT id, account_id, name, target_ratio, created_at, updated_at
+            SELECT id, account_id, name, target_ratio, version, created_at, updated_at
             FROM asset_categories
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
         Ok(category)
     }
 
     pub async fn update(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         account_id: &str,
         name: String,
         target_ratio: Decimal,
+        version: i64,
     ) -> AppResult<AssetCategory> {
         let result = sqlx::query(
             r#"
             UPDATE asset_categories
             SET name = ?, target_ratio = ?, updated_at = CURRENT_TIMESTAMP
-            WHERE id = ? AND account_id = ?
+            WHERE id = ? AND account_id = ? AND version = ? AND deleted_at IS NULL
             "#,
         )
         .bind(name)
-        .bind(target_ratio.to_f64().unwrap_or(0.0))
+        .bind(target_ratio.to_string())
         .bind(id)
         .bind(account_id)
-        .execute(pool)
+        .bind(version)
+        .execute(&mut *conn)
         .await?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(
-                "Category not found or access denied".into(),
-            ));
+            return match Self::find_by_id(conn, id)
+                .await?
+                .filter(|category| category.account_id == account_id)
+            {
+                Some(current) => Err(precondition_failed(&current)),
+                None => Err(AppError::NotFound(
+                    "Category not found or access denied".into(),
+                )),
+            };
         }
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Category not found after update".into()))
     }
 
-    pub async fn delete(pool: &DbPool, id: &str, account_id: &str) -> AppResult<()> {
+    pub async fn delete(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
         let result = sqlx::query(
             r#"
-            DELETE FROM asset_categories
-            WHERE id = ? AND account_id = ?
+            UPDATE asset_categories
+            SET deleted_at = ?
+            WHERE id = ? AND account_id = ? AND deleted_at IS NULL
             "#,
         )
+        .bind(deleted_at)
         .bind(id)
         .bind(account_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
         if result.rows_affected() == 0 {
@@ -118,6 +137,104 @@ impl AssetCategoryRepository {
 
         Ok(())
     }
+
+    /// When the category was soft-deleted, or None if it is active or missing
+    pub async fn find_deleted_at(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<Option<DateTime<Utc>>> {
+        let deleted_at = query_scalar(
+            r#"
+            SELECT deleted_at
+            FROM asset_categories
+            WHERE id = ? AND account_id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(deleted_at)
+    }
+
+    pub async fn restore(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<AssetCategory> {
+        sqlx::query(
+            r#"
+            UPDATE asset_categories
+            SET deleted_at = NULL
+            WHERE id = ? AND account_id = ?
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Self::find_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Category not found after restore".into()))
+    }
+
+    pub async fn delete_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE asset_categories
+            SET deleted_at = ?
+            WHERE account_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(deleted_at)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_deleted_before(conn: &mut DbConn, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM asset_categories
+            WHERE deleted_at IS NOT NULL AND deleted_at < ?
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Restores the categories deleted together with the account at `deleted_at`
+    pub async fn restore_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE asset_categories
+            SET deleted_at = NULL
+            WHERE account_id = ? AND deleted_at = ?
+            "#,
+        )
+        .bind(account_id)
+        .bind(deleted_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
 }
 
 // --- Asset Groupings Repository ---
@@ -126,104 +243,144 @@ pub struct AssetGroupingRepository;
 
 impl AssetGroupingRepository {
     pub async fn create(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         account_id: &str,
         asset_master_id: String,
         category_id: String,
+        weight: Decimal,
     ) -> AppResult<UserAssetGrouping> {
         sqlx::query(
             r#"
-            INSERT INTO user_asset_groupings (id, account_id, asset_master_id, category_id)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO user_asset_groupings (id, account_id, asset_master_id, category_id, weight)
+            VALUES (?, ?, ?, ?, ?)
             "#,
         )
         .bind(id)
         .bind(account_id)
         .bind(asset_master_id)
         .bind(category_id)
-        .execute(pool)
+        .bind(weight.to_string())
+        .execute(&mut *conn)
         .await?;
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Created grouping not found".into()))
     }
 
     pub async fn find_by_account(
-        pool: &DbPool,
+        conn: &mut DbConn,
         account_id: &str,
     ) -> AppResult<Vec<UserAssetGrouping>> {
         let groupings = query_as::<_, UserAssetGrouping>(
             r#"
-            SELECT id, account_id, asset_master_id, category_id, created_at, updated_at
+            SELECT id, account_id, asset_master_id, category_id, weight, version, created_at, updated_at
             FROM user_asset_groupings
-            WHERE account_id = ?
+            WHERE account_id = ? AND deleted_at IS NULL
             ORDER BY created_at DESC
             "#,
         )
         .bind(account_id)
-        .fetch_all(pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(groupings)
     }
 
-    pub async fn find_by_id(pool: &DbPool, id: &str) -> AppResult<Option<UserAssetGrouping>> {
+    pub async fn find_by_id(conn: &mut DbConn, id: &str) -> AppResult<Option<UserAssetGrouping>> {
         let grouping = query_as::<_, UserAssetGrouping>(
             r#"
-            SELECT id, account_id, asset_master_id, category_id, created_at, updated_at
+            SELECT id, account_id, asset_master_id, category_id, weight, version, created_at, updated_at
             FROM user_asset_groupings
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
         Ok(grouping)
     }
 
+    pub async fn find_by_asset(
+        conn: &mut DbConn,
+        account_id: &str,
+        asset_master_id: &str,
+    ) -> AppResult<Vec<UserAssetGrouping>> {
+        let groupings = query_as::<_, UserAssetGrouping>(
+            r#"
+            SELECT id, account_id, asset_master_id, category_id, weight, version, created_at, updated_at
+            FROM user_asset_groupings
+            WHERE account_id = ? AND asset_master_id = ? AND deleted_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(account_id)
+        .bind(asset_master_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(groupings)
+    }
+
     pub async fn update(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         account_id: &str,
         category_id: String,
+        weight: Decimal,
+        version: i64,
     ) -> AppResult<UserAssetGrouping> {
         let result = sqlx::query(
             r#"
             UPDATE user_asset_groupings
-            SET category_id = ?, updated_at = CURRENT_TIMESTAMP
-            WHERE id = ? AND account_id = ?
+            SET category_id = ?, weight = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND account_id = ? AND version = ? AND deleted_at IS NULL
             "#,
         )
         .bind(category_id)
+        .bind(weight.to_string())
         .bind(id)
         .bind(account_id)
-        .execute(pool)
+        .bind(version)
+        .execute(&mut *conn)
         .await?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(
-                "Grouping not found or access denied".into(),
-            ));
+            return match Self::find_by_id(conn, id)
+                .await?
+                .filter(|grouping| grouping.account_id == account_id)
+            {
+                Some(current) => Err(precondition_failed(&current)),
+                None => Err(AppError::NotFound(
+                    "Grouping not found or access denied".into(),
+                )),
+            };
         }
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Grouping not found after update".into()))
     }
 
-    pub async fn delete(pool: &DbPool, id: &str, account_id: &str) -> AppResult<()> {
+    pub async fn delete(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
         let result = sqlx::query(
             r#"
-            DELETE FROM user_asset_groupings
-            WHERE id = ? AND account_id = ?
+            UPDATE user_asset_groupings
+            SET deleted_at = ?
+            WHERE id = ? AND account_id = ? AND deleted_at IS NULL
             "#,
         )
+        .bind(deleted_at)
         .bind(id)
         .bind(account_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
         if result.rows_affected() == 0 {
@@ -234,6 +391,200 @@ impl AssetGroupingRepository {
 
         Ok(())
     }
+
+    /// When the grouping was soft-deleted, or None if it is active or missing
+    pub async fn find_deleted_at(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<Option<DateTime<Utc>>> {
+        let deleted_at = query_scalar(
+            r#"
+            SELECT deleted_at
+            FROM user_asset_groupings
+            WHERE id = ? AND account_id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(deleted_at)
+    }
+
+    /// Fails with a conflict when an active grouping has taken the same asset and category
+    pub async fn restore(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<UserAssetGrouping> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_asset_groupings
+            SET deleted_at = NULL
+            WHERE id = ? AND account_id = ?
+              AND NOT EXISTS (
+                SELECT 1 FROM user_asset_groupings AS active
+                WHERE active.account_id = user_asset_groupings.account_id
+                  AND active.asset_master_id = user_asset_groupings.asset_master_id
+                  AND active.category_id = user_asset_groupings.category_id
+                  AND active.deleted_at IS NULL
+              )
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict(
+                "An active grouping already assigns the asset to this category".into(),
+            ));
+        }
+
+        Self::find_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Grouping not found after restore".into()))
+    }
+
+    pub async fn delete_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE user_asset_groupings
+            SET deleted_at = ?
+            WHERE account_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(deleted_at)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_deleted_before(conn: &mut DbConn, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM user_asset_groupings
+            WHERE deleted_at IS NOT NULL AND deleted_at < ?
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Restores groupings deleted together with their account, skipping any that an
+    /// active grouping has replaced since
+    pub async fn restore_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE user_asset_groupings
+            SET deleted_at = NULL
+            WHERE account_id = ? AND deleted_at = ?
+              AND NOT EXISTS (
+                SELECT 1 FROM user_asset_groupings AS active
+                WHERE active.account_id = user_asset_groupings.account_id
+                  AND active.asset_master_id = user_asset_groupings.asset_master_id
+                  AND active.category_id = user_asset_groupings.category_id
+                  AND active.deleted_at IS NULL
+              )
+            "#,
+        )
+        .bind(account_id)
+        .bind(deleted_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_by_category(
+        conn: &mut DbConn,
+        account_id: &str,
+        category_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE user_asset_groupings
+            SET deleted_at = ?
+            WHERE account_id = ? AND category_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(deleted_at)
+        .bind(account_id)
+        .bind(category_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Restores the groupings deleted together with the category at `deleted_at`
+    pub async fn restore_by_category(
+        conn: &mut DbConn,
+        account_id: &str,
+        category_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE user_asset_groupings
+            SET deleted_at = NULL
+            WHERE account_id = ? AND category_id = ? AND deleted_at = ?
+              AND NOT EXISTS (
+                SELECT 1 FROM user_asset_groupings AS active
+                WHERE active.account_id = user_asset_groupings.account_id
+                  AND active.asset_master_id = user_asset_groupings.asset_master_id
+                  AND active.category_id = user_asset_groupings.category_id
+                  AND active.deleted_at IS NULL
+              )
+            "#,
+        )
+        .bind(account_id)
+        .bind(category_id)
+        .bind(deleted_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_by_asset(
+        conn: &mut DbConn,
+        account_id: &str,
+        asset_master_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE user_asset_groupings
+            SET deleted_at = ?
+            WHERE account_id = ? AND asset_master_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(deleted_at)
+        .bind(account_id)
+        .bind(asset_master_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
 }
 
 // --- Assets (Holdings) Repository ---
@@ -242,101 +593,144 @@ pub struct AssetRepository;
 
 impl AssetRepository {
     pub async fn create(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         account_id: &str,
         asset_master_id: String,
         current_amount: Decimal,
+        is_liability: bool,
     ) -> AppResult<Asset> {
         sqlx::query(
             r#"
-            INSERT INTO assets (id, account_id, asset_master_id, current_amount)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO assets (id, account_id, asset_master_id, current_amount, is_liability)
+            VALUES (?, ?, ?, ?, ?)
             "#,
         )
         .bind(id)
         .bind(account_id)
         .bind(asset_master_id)
-        .bind(current_amount.to_f64().unwrap_or(0.0))
-        .execute(pool)
+        .bind(current_amount.to_string())
+        .bind(is_liability)
+        .execute(&mut *conn)
         .await?;
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Created asset not found".into()))
     }
 
-    pub async fn find_by_account(pool: &DbPool, account_id: &str) -> AppResult<Vec<Asset>> {
+    pub async fn find_by_account(conn: &mut DbConn, account_id: &str) -> AppResult<Vec<Asset>> {
         let assets = query_as::<_, Asset>(
             r#"
-            SELECT id, account_id, asset_master_id, current_amount, created_at, updated_at
+            SELECT id, account_id, asset_master_id, current_amount, is_liability, version, created_at,
+                updated_at
             FROM assets
-            WHERE account_id = ?
+            WHERE account_id = ? AND deleted_at IS NULL
             ORDER BY created_at DESC
             "#,
         )
         .bind(account_id)
-        .fetch_all(pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(assets)
     }
 
-    pub async fn find_by_id(pool: &DbPool, id: &str) -> AppResult<Option<Asset>> {
+    pub async fn find_by_id(conn: &mut DbConn, id: &str) -> AppResult<Option<Asset>> {
         let asset = query_as::<_, Asset>(
             r#"
-            SELECT id, account_id, asset_master_id, current_amount, created_at, updated_at
+            SELECT id, account_id, asset_master_id, current_amount, is_liability, version, created_at,
+                updated_at
             FROM assets
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(asset)
+    }
+
+    /// The active holding of one asset; an account holds each asset at most once
+    pub async fn find_by_asset_master(
+        conn: &mut DbConn,
+        account_id: &str,
+        asset_master_id: &str,
+    ) -> AppResult<Option<Asset>> {
+        let asset = query_as::<_, Asset>(
+            r#"
+            SELECT id, account_id, asset_master_id, current_amount, is_liability, version, created_at,
+                updated_at
+            FROM assets
+            WHERE account_id = ? AND asset_master_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(account_id)
+        .bind(asset_master_id)
+        .fetch_optional(&mut *conn)
         .await?;
 
         Ok(asset)
     }
 
     pub async fn update(
-        pool: &DbPool,
+        conn: &mut DbConn,
         id: &str,
         account_id: &str,
         current_amount: Decimal,
+        is_liability: bool,
+        version: i64,
     ) -> AppResult<Asset> {
         let result = sqlx::query(
             r#"
             UPDATE assets
-            SET current_amount = ?, updated_at = CURRENT_TIMESTAMP
-            WHERE id = ? AND account_id = ?
+            SET current_amount = ?, is_liability = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND account_id = ? AND version = ? AND deleted_at IS NULL
             "#,
         )
-        .bind(current_amount.to_f64().unwrap_or(0.0))
+        .bind(current_amount.to_string())
+        .bind(is_liability)
         .bind(id)
         .bind(account_id)
-        .execute(pool)
+        .bind(version)
+        .execute(&mut *conn)
         .await?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(
-                "Asset not found or access denied".into(),
-            ));
+            return match Self::find_by_id(conn, id)
+                .await?
+                .filter(|asset| asset.account_id == account_id)
+            {
+                Some(current) => Err(precondition_failed(&current)),
+                None => Err(AppError::NotFound(
+                    "Asset not found or access denied".into(),
+                )),
+            };
         }
 
-        Self::find_by_id(pool, id)
+        Self::find_by_id(conn, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Asset not found after update".into()))
     }
 
-    pub async fn delete(pool: &DbPool, id: &str, account_id: &str) -> AppResult<()> {
+    pub async fn delete(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
         let result = sqlx::query(
             r#"
-            DELETE FROM assets
-            WHERE id = ? AND account_id = ?
+            UPDATE assets
+            SET deleted_at = ?
+            WHERE id = ? AND account_id = ? AND deleted_at IS NULL
             "#,
         )
+        .bind(deleted_at)
         .bind(id)
         .bind(account_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
         if result.rows_affected() == 0 {
@@ -347,4 +741,118 @@ impl AssetRepository {
 
         Ok(())
     }
+
+    /// When the asset was soft-deleted, or None if it is active or missing
+    pub async fn find_deleted_at(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<Option<DateTime<Utc>>> {
+        let deleted_at = query_scalar(
+            r#"
+            SELECT deleted_at
+            FROM assets
+            WHERE id = ? AND account_id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(deleted_at)
+    }
+
+    /// Fails with a conflict when the account holds the same asset again
+    pub async fn restore(conn: &mut DbConn, id: &str, account_id: &str) -> AppResult<Asset> {
+        let result = sqlx::query(
+            r#"
+            UPDATE assets
+            SET deleted_at = NULL
+            WHERE id = ? AND account_id = ?
+              AND NOT EXISTS (
+                SELECT 1 FROM assets AS active
+                WHERE active.account_id = assets.account_id
+                  AND active.asset_master_id = assets.asset_master_id
+                  AND active.deleted_at IS NULL
+              )
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict(
+                "The account already holds this asset".into(),
+            ));
+        }
+
+        Self::find_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Asset not found after restore".into()))
+    }
+
+    pub async fn delete_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE assets
+            SET deleted_at = ?
+            WHERE account_id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(deleted_at)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_deleted_before(conn: &mut DbConn, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM assets
+            WHERE deleted_at IS NOT NULL AND deleted_at < ?
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Restores holdings deleted together with their account, skipping assets that
+    /// have an active holding again
+    pub async fn restore_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE assets
+            SET deleted_at = NULL
+            WHERE account_id = ? AND deleted_at = ?
+              AND NOT EXISTS (
+                SELECT 1 FROM assets AS active
+                WHERE active.account_id = assets.account_id
+                  AND active.asset_master_id = assets.asset_master_id
+                  AND active.deleted_at IS NULL
+              )
+            "#,
+        )
+        .bind(account_id)
+        .bind(deleted_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
 }
@@ -1,7 +1,10 @@
-use crate::shared::validation::validate_non_blank;
+use crate::domains::catalog::model::CompositionDimension;
+use crate::shared::validation::{
+    validate_holding_amount, validate_non_blank, validate_target_ratio, validate_weight,
+};
 use rust_decimal::Decimal;
 use serde::Deserialize;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 // Asset Categories
 #[derive(Deserialize, Validate, utoipa::ToSchema)]
@@ -11,6 +14,10 @@ pub struct CreateCategoryRequest {
         message = "Name cannot be empty or blank"
     ))]
     pub name: String,
+    #[validate(custom(
+        function = "validate_target_ratio",
+        message = "Target ratio must be between 0 and 100 with at most 2 decimal places"
+    ))]
     pub target_ratio: Decimal,
 }
 
@@ -21,9 +28,28 @@ pub struct UpdateCategoryRequest {
         message = "Name cannot be empty or blank"
     ))]
     pub name: String,
+    #[validate(custom(
+        function = "validate_target_ratio",
+        message = "Target ratio must be between 0 and 100 with at most 2 decimal places"
+    ))]
     pub target_ratio: Decimal,
 }
 
+/// Omit `name` or `target_ratio` to keep it
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct PatchCategoryRequest {
+    #[validate(custom(
+        function = "validate_non_blank",
+        message = "Name cannot be empty or blank"
+    ))]
+    pub name: Option<String>,
+    #[validate(custom(
+        function = "validate_target_ratio",
+        message = "Target ratio must be between 0 and 100 with at most 2 decimal places"
+    ))]
+    pub target_ratio: Option<Decimal>,
+}
+
 // User Asset Groupings
 #[derive(Deserialize, Validate, utoipa::ToSchema)]
 pub struct CreateGroupingRequest {
@@ -37,6 +63,13 @@ pub struct CreateGroupingRequest {
         message = "Category ID cannot be empty or blank"
     ))]
     pub category_id: String,
+    /// Share of the asset assigned to the category (%). Defaults to 100.
+    #[serde(default = "full_weight")]
+    #[validate(custom(
+        function = "validate_weight",
+        message = "Weight must be greater than 0 and at most 100"
+    ))]
+    pub weight: Decimal,
 }
 
 #[derive(Deserialize, Validate, utoipa::ToSchema)]
@@ -46,6 +79,75 @@ pub struct UpdateGroupingRequest {
         message = "Category ID cannot be empty or blank"
     ))]
     pub category_id: String,
+    #[serde(default = "full_weight")]
+    #[validate(custom(
+        function = "validate_weight",
+        message = "Weight must be greater than 0 and at most 100"
+    ))]
+    pub weight: Decimal,
+}
+
+/// Moves the grouping to another category, changes its weight, or both
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct PatchGroupingRequest {
+    #[validate(custom(
+        function = "validate_non_blank",
+        message = "Category ID cannot be empty or blank"
+    ))]
+    pub category_id: Option<String>,
+    #[validate(custom(
+        function = "validate_weight",
+        message = "Weight must be greater than 0 and at most 100"
+    ))]
+    pub weight: Option<Decimal>,
+}
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct GroupingSplitItem {
+    #[validate(custom(
+        function = "validate_non_blank",
+        message = "Category ID cannot be empty or blank"
+    ))]
+    pub category_id: String,
+    #[validate(custom(
+        function = "validate_weight",
+        message = "Weight must be greater than 0 and at most 100"
+    ))]
+    pub weight: Decimal,
+}
+
+/// Replaces every grouping of one asset with a weighted split
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+#[validate(schema(function = "validate_split_total"))]
+pub struct ReplaceGroupingSplitRequest {
+    #[validate(nested)]
+    pub splits: Vec<GroupingSplitItem>,
+}
+
+fn full_weight() -> Decimal {
+    Decimal::ONE_HUNDRED
+}
+
+fn validate_split_total(request: &ReplaceGroupingSplitRequest) -> Result<(), ValidationError> {
+    let total: Decimal = request.splits.iter().map(|split| split.weight).sum();
+    if total != Decimal::ONE_HUNDRED {
+        return Err(ValidationError::new("weight_total")
+            .with_message("Weights of an asset must sum to 100".into()));
+    }
+
+    let mut category_ids: Vec<&str> = request
+        .splits
+        .iter()
+        .map(|split| split.category_id.as_str())
+        .collect();
+    category_ids.sort_unstable();
+    category_ids.dedup();
+    if category_ids.len() != request.splits.len() {
+        return Err(ValidationError::new("duplicate_category")
+            .with_message("Each category may appear only once".into()));
+    }
+
+    Ok(())
 }
 
 // Assets (Holdings)
@@ -56,10 +158,82 @@ pub struct CreateUserAssetRequest {
         message = "Asset Master ID cannot be empty or blank"
     ))]
     pub asset_master_id: String,
+    #[validate(custom(
+        function = "validate_holding_amount",
+        message = "Amount must be zero or positive with at most 4 decimal places"
+    ))]
     pub current_amount: Decimal,
+    /// Record a short position or loan; the amount is then what is owed
+    #[serde(default)]
+    pub is_liability: bool,
 }
 
 #[derive(Deserialize, Validate, utoipa::ToSchema)]
 pub struct UpdateUserAssetRequest {
+    #[validate(custom(
+        function = "validate_holding_amount",
+        message = "Amount must be zero or positive with at most 4 decimal places"
+    ))]
+    pub current_amount: Decimal,
+    #[serde(default)]
+    pub is_liability: bool,
+}
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct PatchUserAssetRequest {
+    #[validate(custom(
+        function = "validate_holding_amount",
+        message = "Amount must be zero or positive with at most 4 decimal places"
+    ))]
+    pub current_amount: Option<Decimal>,
+    pub is_liability: Option<bool>,
+}
+
+/// One line of a statement. Identify the asset by either `asset_master_id` or `ticker_symbol`.
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+#[validate(schema(function = "validate_holding_reference"))]
+pub struct BatchHoldingEntry {
+    pub asset_master_id: Option<String>,
+    pub ticker_symbol: Option<String>,
+    #[validate(custom(
+        function = "validate_holding_amount",
+        message = "Amount must be zero or positive with at most 4 decimal places"
+    ))]
     pub current_amount: Decimal,
 }
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct BatchUpsertHoldingsRequest {
+    #[validate(nested)]
+    pub holdings: Vec<BatchHoldingEntry>,
+    /// Delete holdings that are not in `holdings`
+    #[serde(default)]
+    pub delete_missing: bool,
+    /// Only compute the diff without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn validate_holding_reference(entry: &BatchHoldingEntry) -> Result<(), ValidationError> {
+    let has_id = entry
+        .asset_master_id
+        .as_deref()
+        .is_some_and(|id| !id.trim().is_empty());
+    let has_ticker = entry
+        .ticker_symbol
+        .as_deref()
+        .is_some_and(|ticker| !ticker.trim().is_empty());
+
+    if has_id == has_ticker {
+        return Err(ValidationError::new("holding_reference")
+            .with_message("Specify exactly one of asset_master_id or ticker_symbol".into()));
+    }
+    Ok(())
+}
+
+// Look-through
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CompositionQuery {
+    pub dimension: CompositionDimension,
+}
@@ -1,11 +1,24 @@
-use crate::core::database::DbPool;
-use crate::core::error::AppResult;
-use crate::domains::portfolio::model::{Asset, AssetCategory, UserAssetGrouping};
+use std::collections::HashMap;
+
+use crate::core::database::{DbConn, DbPool, transaction};
+use crate::core::error::{AppError, AppResult};
+use crate::domains::audit::model::AuditEntity;
+use crate::domains::audit::service::AuditService;
+use crate::domains::catalog::model::{AssetComposition, CompositionDimension};
+use crate::domains::catalog::repository::AssetMasterRepository;
+use crate::domains::portfolio::model::{
+    Asset, AssetCategory, CategoryAllocation, Exposure, GroupingSuggestion, HoldingChange,
+    HoldingsDiff, LookThroughReport, PortfolioAllocation, UserAssetGrouping,
+};
 use crate::domains::portfolio::repository::{
     AssetCategoryRepository, AssetGroupingRepository, AssetRepository,
 };
+use crate::domains::portfolio::schema::BatchHoldingEntry;
+use crate::shared::etag::ensure_version;
 use crate::shared::util::generate_id;
+use chrono::Utc;
 use rust_decimal::Decimal;
+use validator::{ValidationError, ValidationErrors};
 
 // --- Asset Category Service ---
 
@@ -19,29 +32,104 @@ impl AssetCategoryService {
         target_ratio: Decimal,
     ) -> AppResult<AssetCategory> {
         let id = generate_id();
-        AssetCategoryRepository::create(pool, &id, account_id, name, target_ratio).await
+        transaction(pool, async |conn| {
+            let category =
+                AssetCategoryRepository::create(conn, &id, account_id, name, target_ratio).await?;
+            AuditService::created(
+                conn,
+                Some(account_id),
+                AuditEntity::Category,
+                &id,
+                &category,
+            )
+            .await?;
+            Ok(category)
+        })
+        .await
     }
 
     pub async fn get_by_account(pool: &DbPool, account_id: &str) -> AppResult<Vec<AssetCategory>> {
-        AssetCategoryRepository::find_by_account(pool, account_id).await
+        let mut conn = pool.acquire().await?;
+        AssetCategoryRepository::find_by_account(&mut conn, account_id).await
     }
 
     pub async fn get_by_id(pool: &DbPool, id: &str) -> AppResult<Option<AssetCategory>> {
-        AssetCategoryRepository::find_by_id(pool, id).await
+        let mut conn = pool.acquire().await?;
+        AssetCategoryRepository::find_by_id(&mut conn, id).await
     }
 
     pub async fn update(
         pool: &DbPool,
         id: &str,
         account_id: &str,
-        name: String,
-        target_ratio: Decimal,
+        name: Option<String>,
+        target_ratio: Option<Decimal>,
+        expected_version: Option<i64>,
     ) -> AppResult<AssetCategory> {
-        AssetCategoryRepository::update(pool, id, account_id, name, target_ratio).await
+        transaction(pool, async |conn| {
+            let before = AssetCategoryRepository::find_by_id(conn, id)
+                .await?
+                .filter(|category| category.account_id == account_id)
+                .ok_or_else(|| AppError::NotFound("Category not found or access denied".into()))?;
+            ensure_version(expected_version, before.version, &before)?;
+
+            let name = name.unwrap_or_else(|| before.name.clone());
+            let target_ratio = target_ratio.unwrap_or(before.target_ratio);
+            let category = AssetCategoryRepository::update(
+                conn,
+                id,
+                account_id,
+                name,
+                target_ratio,
+                before.version,
+            )
+            .await?;
+            AuditService::updated(
+                conn,
+                Some(account_id),
+                AuditEntity::Category,
+                id,
+                Some(&before),
+                &category,
+            )
+            .await?;
+            Ok(category)
+        })
+        .await
     }
 
+    /// Soft-deletes the category together with its groupings
     pub async fn delete(pool: &DbPool, id: &str, account_id: &str) -> AppResult<()> {
-        AssetCategoryRepository::delete(pool, id, account_id).await
+        let deleted_at = Utc::now();
+        transaction(pool, async |conn| {
+            let before = AssetCategoryRepository::find_by_id(conn, id).await?;
+            AssetCategoryRepository::delete(conn, id, account_id, deleted_at).await?;
+            AssetGroupingRepository::delete_by_category(conn, account_id, id, deleted_at).await?;
+            AuditService::deleted(
+                conn,
+                Some(account_id),
+                AuditEntity::Category,
+                id,
+                before.as_ref(),
+            )
+            .await
+        })
+        .await
+    }
+
+    /// Restores the category and the groupings that were deleted with it
+    pub async fn restore(pool: &DbPool, id: &str, account_id: &str) -> AppResult<AssetCategory> {
+        transaction(pool, async |conn| {
+            let deleted_at = AssetCategoryRepository::find_deleted_at(conn, id, account_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Deleted category not found".into()))?;
+            let category = AssetCategoryRepository::restore(conn, id, account_id).await?;
+            AssetGroupingRepository::restore_by_category(conn, account_id, id, deleted_at).await?;
+            AuditService::restored(conn, Some(account_id), AuditEntity::Category, id, &category)
+                .await?;
+            Ok(category)
+        })
+        .await
     }
 }
 
@@ -55,34 +143,307 @@ impl AssetGroupingService {
         account_id: &str,
         asset_master_id: String,
         category_id: String,
+        weight: Decimal,
     ) -> AppResult<UserAssetGrouping> {
         let id = generate_id();
-        AssetGroupingRepository::create(pool, &id, account_id, asset_master_id, category_id).await
+        transaction(pool, async |conn| {
+            ensure_catalog_asset(conn, &asset_master_id).await?;
+            ensure_own_category(conn, account_id, &category_id).await?;
+            let assigned = Self::assigned_weight(conn, account_id, &asset_master_id, None).await?;
+            if assigned + weight > Decimal::ONE_HUNDRED {
+                return Err(weight_overflow_error());
+            }
+
+            let grouping = AssetGroupingRepository::create(
+                conn,
+                &id,
+                account_id,
+                asset_master_id,
+                category_id,
+                weight,
+            )
+            .await?;
+            AuditService::created(
+                conn,
+                Some(account_id),
+                AuditEntity::Grouping,
+                &id,
+                &grouping,
+            )
+            .await?;
+            Ok(grouping)
+        })
+        .await
     }
 
     pub async fn get_by_account(
         pool: &DbPool,
         account_id: &str,
     ) -> AppResult<Vec<UserAssetGrouping>> {
-        AssetGroupingRepository::find_by_account(pool, account_id).await
+        let mut conn = pool.acquire().await?;
+        AssetGroupingRepository::find_by_account(&mut conn, account_id).await
     }
 
     pub async fn get_by_id(pool: &DbPool, id: &str) -> AppResult<Option<UserAssetGrouping>> {
-        AssetGroupingRepository::find_by_id(pool, id).await
+        let mut conn = pool.acquire().await?;
+        AssetGroupingRepository::find_by_id(&mut conn, id).await
     }
 
     pub async fn update(
         pool: &DbPool,
         id: &str,
         account_id: &str,
-        category_id: String,
+        category_id: Option<String>,
+        weight: Option<Decimal>,
+        expected_version: Option<i64>,
     ) -> AppResult<UserAssetGrouping> {
-        AssetGroupingRepository::update(pool, id, account_id, category_id).await
+        transaction(pool, async |conn| {
+            let before = AssetGroupingRepository::find_by_id(conn, id)
+                .await?
+                .filter(|grouping| grouping.account_id == account_id)
+                .ok_or_else(|| AppError::NotFound("Grouping not found or access denied".into()))?;
+            ensure_version(expected_version, before.version, &before)?;
+
+            let category_id = category_id.unwrap_or_else(|| before.category_id.clone());
+            if category_id != before.category_id {
+                ensure_own_category(conn, account_id, &category_id).await?;
+            }
+            let weight = weight.unwrap_or(before.weight);
+            let assigned =
+                Self::assigned_weight(conn, account_id, &before.asset_master_id, Some(id)).await?;
+            if assigned + weight > Decimal::ONE_HUNDRED {
+                return Err(weight_overflow_error());
+            }
+
+            let grouping = AssetGroupingRepository::update(
+                conn,
+                id,
+                account_id,
+                category_id,
+                weight,
+                before.version,
+            )
+            .await?;
+            AuditService::updated(
+                conn,
+                Some(account_id),
+                AuditEntity::Grouping,
+                id,
+                Some(&before),
+                &grouping,
+            )
+            .await?;
+            Ok(grouping)
+        })
+        .await
     }
 
     pub async fn delete(pool: &DbPool, id: &str, account_id: &str) -> AppResult<()> {
-        AssetGroupingRepository::delete(pool, id, account_id).await
+        transaction(pool, async |conn| {
+            let before = AssetGroupingRepository::find_by_id(conn, id).await?;
+            AssetGroupingRepository::delete(conn, id, account_id, Utc::now()).await?;
+            AuditService::deleted(
+                conn,
+                Some(account_id),
+                AuditEntity::Grouping,
+                id,
+                before.as_ref(),
+            )
+            .await
+        })
+        .await
+    }
+
+    /// Restores a grouping unless its category is gone or the asset's weights would
+    /// exceed 100 again
+    pub async fn restore(
+        pool: &DbPool,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<UserAssetGrouping> {
+        transaction(pool, async |conn| {
+            AssetGroupingRepository::find_deleted_at(conn, id, account_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Deleted grouping not found".into()))?;
+            let grouping = AssetGroupingRepository::restore(conn, id, account_id).await?;
+
+            if AssetCategoryRepository::find_by_id(conn, &grouping.category_id)
+                .await?
+                .is_none()
+            {
+                return Err(AppError::Conflict(
+                    "The grouping's category is deleted; restore the category first".into(),
+                ));
+            }
+            let assigned =
+                Self::assigned_weight(conn, account_id, &grouping.asset_master_id, None).await?;
+            if assigned > Decimal::ONE_HUNDRED {
+                return Err(weight_overflow_error());
+            }
+
+            AuditService::restored(conn, Some(account_id), AuditEntity::Grouping, id, &grouping)
+                .await?;
+            Ok(grouping)
+        })
+        .await
+    }
+
+    pub async fn get_split(
+        pool: &DbPool,
+        account_id: &str,
+        asset_master_id: &str,
+    ) -> AppResult<Vec<UserAssetGrouping>> {
+        let mut conn = pool.acquire().await?;
+        AssetGroupingRepository::find_by_asset(&mut conn, account_id, asset_master_id).await
+    }
+
+    /// `splits` is a list of (category_id, weight) whose weights sum to 100
+    pub async fn replace_split(
+        pool: &DbPool,
+        account_id: &str,
+        asset_master_id: &str,
+        splits: Vec<(String, Decimal)>,
+    ) -> AppResult<Vec<UserAssetGrouping>> {
+        transaction(pool, async |conn| {
+            ensure_catalog_asset(conn, asset_master_id).await?;
+            for (category_id, _) in &splits {
+                ensure_own_category(conn, account_id, category_id).await?;
+            }
+            let replaced =
+                AssetGroupingRepository::find_by_asset(conn, account_id, asset_master_id).await?;
+            AssetGroupingRepository::delete_by_asset(conn, account_id, asset_master_id, Utc::now())
+                .await?;
+            for grouping in &replaced {
+                AuditService::deleted(
+                    conn,
+                    Some(account_id),
+                    AuditEntity::Grouping,
+                    &grouping.id,
+                    Some(grouping),
+                )
+                .await?;
+            }
+            for (category_id, weight) in splits {
+                let grouping = AssetGroupingRepository::create(
+                    conn,
+                    &generate_id(),
+                    account_id,
+                    asset_master_id.to_string(),
+                    category_id,
+                    weight,
+                )
+                .await?;
+                AuditService::created(
+                    conn,
+                    Some(account_id),
+                    AuditEntity::Grouping,
+                    &grouping.id,
+                    &grouping,
+                )
+                .await?;
+            }
+            AssetGroupingRepository::find_by_asset(conn, account_id, asset_master_id).await
+        })
+        .await
+    }
+
+    /// Proposes a weighted split from the catalog breakdown, matching labels to category names
+    pub async fn suggest_split(
+        pool: &DbPool,
+        account_id: &str,
+        asset_master_id: &str,
+        dimension: CompositionDimension,
+    ) -> AppResult<Vec<GroupingSuggestion>> {
+        let mut conn = pool.acquire().await?;
+        let composition =
+            AssetMasterRepository::find_composition(&mut conn, asset_master_id).await?;
+        let categories = AssetCategoryRepository::find_by_account(&mut conn, account_id).await?;
+
+        let suggestions = composition
+            .into_iter()
+            .filter(|item| item.dimension == dimension)
+            .map(|item| {
+                let category = categories.iter().find(|category| {
+                    category.name.trim().to_lowercase() == item.label.trim().to_lowercase()
+                });
+                GroupingSuggestion {
+                    label: item.label,
+                    weight: item.weight,
+                    category_id: category.map(|category| category.id.clone()),
+                    category_name: category.map(|category| category.name.clone()),
+                }
+            })
+            .collect();
+
+        Ok(suggestions)
+    }
+
+    /// Sum of weights already assigned to an asset, optionally ignoring one grouping
+    async fn assigned_weight(
+        conn: &mut DbConn,
+        account_id: &str,
+        asset_master_id: &str,
+        exclude_id: Option<&str>,
+    ) -> AppResult<Decimal> {
+        let groupings =
+            AssetGroupingRepository::find_by_asset(conn, account_id, asset_master_id).await?;
+        Ok(groupings
+            .iter()
+            .filter(|grouping| Some(grouping.id.as_str()) != exclude_id)
+            .map(|grouping| grouping.weight)
+            .sum())
+    }
+}
+
+fn weight_overflow_error() -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        "weight",
+        ValidationError::new("weight_total")
+            .with_message("Weights of an asset cannot exceed 100 in total".into()),
+    );
+    AppError::Validation(errors)
+}
+
+/// Rejects references to assets missing from the catalog instead of storing them dangling
+async fn ensure_catalog_asset(conn: &mut DbConn, asset_master_id: &str) -> AppResult<()> {
+    if AssetMasterRepository::find_by_id(conn, asset_master_id)
+        .await?
+        .is_none()
+    {
+        return Err(reference_error(
+            "asset_master_id",
+            format!("Asset {asset_master_id} does not exist in the catalog"),
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects categories that are deleted, missing or owned by another account
+async fn ensure_own_category(
+    conn: &mut DbConn,
+    account_id: &str,
+    category_id: &str,
+) -> AppResult<()> {
+    let owned = AssetCategoryRepository::find_by_id(conn, category_id)
+        .await?
+        .is_some_and(|category| category.account_id == account_id);
+    if !owned {
+        return Err(reference_error(
+            "category_id",
+            format!("Category {category_id} does not exist for this account"),
+        ));
     }
+    Ok(())
+}
+
+fn reference_error(field: &'static str, message: String) -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        field,
+        ValidationError::new("unknown_reference").with_message(message.into()),
+    );
+    AppError::Unprocessable(errors)
 }
 
 // --- Asset Service ---
@@ -95,29 +456,470 @@ impl AssetService {
         account_id: &str,
         asset_master_id: String,
         current_amount: Decimal,
+        is_liability: bool,
     ) -> AppResult<Asset> {
         let id = generate_id();
-        AssetRepository::create(pool, &id, account_id, asset_master_id, current_amount).await
+        transaction(pool, async |conn| {
+            ensure_catalog_asset(conn, &asset_master_id).await?;
+            if AssetRepository::find_by_asset_master(conn, account_id, &asset_master_id)
+                .await?
+                .is_some()
+            {
+                return Err(AppError::Conflict(
+                    "The account already holds this asset; update that holding instead".into(),
+                ));
+            }
+            let asset = AssetRepository::create(
+                conn,
+                &id,
+                account_id,
+                asset_master_id,
+                current_amount,
+                is_liability,
+            )
+            .await?;
+            AuditService::created(conn, Some(account_id), AuditEntity::Holding, &id, &asset)
+                .await?;
+            Ok(asset)
+        })
+        .await
     }
 
     pub async fn get_by_account(pool: &DbPool, account_id: &str) -> AppResult<Vec<Asset>> {
-        AssetRepository::find_by_account(pool, account_id).await
+        let mut conn = pool.acquire().await?;
+        AssetRepository::find_by_account(&mut conn, account_id).await
     }
 
     pub async fn get_by_id(pool: &DbPool, id: &str) -> AppResult<Option<Asset>> {
-        AssetRepository::find_by_id(pool, id).await
+        let mut conn = pool.acquire().await?;
+        AssetRepository::find_by_id(&mut conn, id).await
     }
 
     pub async fn update(
         pool: &DbPool,
         id: &str,
         account_id: &str,
-        current_amount: Decimal,
+        current_amount: Option<Decimal>,
+        is_liability: Option<bool>,
+        expected_version: Option<i64>,
     ) -> AppResult<Asset> {
-        AssetRepository::update(pool, id, account_id, current_amount).await
+        transaction(pool, async |conn| {
+            let before = AssetRepository::find_by_id(conn, id)
+                .await?
+                .filter(|asset| asset.account_id == account_id)
+                .ok_or_else(|| AppError::NotFound("Asset not found or access denied".into()))?;
+            ensure_version(expected_version, before.version, &before)?;
+
+            let current_amount = current_amount.unwrap_or(before.current_amount);
+            let is_liability = is_liability.unwrap_or(before.is_liability);
+            let asset = AssetRepository::update(
+                conn,
+                id,
+                account_id,
+                current_amount,
+                is_liability,
+                before.version,
+            )
+            .await?;
+            AuditService::updated(
+                conn,
+                Some(account_id),
+                AuditEntity::Holding,
+                id,
+                Some(&before),
+                &asset,
+            )
+            .await?;
+            Ok(asset)
+        })
+        .await
     }
 
     pub async fn delete(pool: &DbPool, id: &str, account_id: &str) -> AppResult<()> {
-        AssetRepository::delete(pool, id, account_id).await
+        transaction(pool, async |conn| {
+            let before = AssetRepository::find_by_id(conn, id).await?;
+            AssetRepository::delete(conn, id, account_id, Utc::now()).await?;
+            AuditService::deleted(
+                conn,
+                Some(account_id),
+                AuditEntity::Holding,
+                id,
+                before.as_ref(),
+            )
+            .await
+        })
+        .await
+    }
+
+    pub async fn restore(pool: &DbPool, id: &str, account_id: &str) -> AppResult<Asset> {
+        transaction(pool, async |conn| {
+            AssetRepository::find_deleted_at(conn, id, account_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Deleted asset not found".into()))?;
+            let asset = AssetRepository::restore(conn, id, account_id).await?;
+            AuditService::restored(conn, Some(account_id), AuditEntity::Holding, id, &asset)
+                .await?;
+            Ok(asset)
+        })
+        .await
+    }
+
+    /// Syncs the account's holdings to `entries` (e.g. a brokerage statement) in one transaction
+    pub async fn sync(
+        pool: &DbPool,
+        account_id: &str,
+        entries: Vec<BatchHoldingEntry>,
+        delete_missing: bool,
+        dry_run: bool,
+    ) -> AppResult<HoldingsDiff> {
+        transaction(pool, async |conn| {
+            let targets = Self::resolve_entries(conn, entries).await?;
+            let existing = AssetRepository::find_by_account(conn, account_id).await?;
+            let mut diff = HoldingsDiff {
+                applied: !dry_run,
+                ..HoldingsDiff::default()
+            };
+
+            for (asset_master_id, amount) in &targets {
+                let holding = existing
+                    .iter()
+                    .find(|holding| &holding.asset_master_id == asset_master_id);
+                match holding {
+                    Some(holding) if holding.current_amount == *amount => diff.unchanged += 1,
+                    Some(holding) => {
+                        if !dry_run {
+                            let updated = AssetRepository::update(
+                                conn,
+                                &holding.id,
+                                account_id,
+                                *amount,
+                                holding.is_liability,
+                                holding.version,
+                            )
+                            .await?;
+                            AuditService::updated(
+                                conn,
+                                Some(account_id),
+                                AuditEntity::Holding,
+                                &holding.id,
+                                Some(holding),
+                                &updated,
+                            )
+                            .await?;
+                        }
+                        diff.updated.push(HoldingChange {
+                            asset_master_id: asset_master_id.clone(),
+                            previous_amount: Some(holding.current_amount),
+                            current_amount: Some(*amount),
+                        });
+                    }
+                    None => {
+                        if !dry_run {
+                            let created = AssetRepository::create(
+                                conn,
+                                &generate_id(),
+                                account_id,
+                                asset_master_id.clone(),
+                                *amount,
+                                false,
+                            )
+                            .await?;
+                            AuditService::created(
+                                conn,
+                                Some(account_id),
+                                AuditEntity::Holding,
+                                &created.id,
+                                &created,
+                            )
+                            .await?;
+                        }
+                        diff.created.push(HoldingChange {
+                            asset_master_id: asset_master_id.clone(),
+                            previous_amount: None,
+                            current_amount: Some(*amount),
+                        });
+                    }
+                }
+            }
+
+            if delete_missing {
+                for holding in existing.iter().filter(|holding| {
+                    !targets
+                        .iter()
+                        .any(|(asset_master_id, _)| asset_master_id == &holding.asset_master_id)
+                }) {
+                    if !dry_run {
+                        AssetRepository::delete(conn, &holding.id, account_id, Utc::now()).await?;
+                        AuditService::deleted(
+                            conn,
+                            Some(account_id),
+                            AuditEntity::Holding,
+                            &holding.id,
+                            Some(holding),
+                        )
+                        .await?;
+                    }
+                    diff.deleted.push(HoldingChange {
+                        asset_master_id: holding.asset_master_id.clone(),
+                        previous_amount: Some(holding.current_amount),
+                        current_amount: None,
+                    });
+                }
+            }
+
+            Ok(diff)
+        })
+        .await
+    }
+
+    /// Resolves each entry to (asset_master_id, amount), rejecting unknown or repeated assets
+    async fn resolve_entries(
+        conn: &mut DbConn,
+        entries: Vec<BatchHoldingEntry>,
+    ) -> AppResult<Vec<(String, Decimal)>> {
+        let mut targets: Vec<(String, Decimal)> = Vec::with_capacity(entries.len());
+        let mut unknown = Vec::new();
+
+        for entry in entries {
+            let asset = match (&entry.asset_master_id, &entry.ticker_symbol) {
+                (Some(id), _) if !id.trim().is_empty() => {
+                    AssetMasterRepository::find_by_id(conn, id.trim()).await?
+                }
+                (_, Some(ticker)) => {
+                    AssetMasterRepository::find_by_ticker(conn, ticker.trim()).await?
+                }
+                _ => None,
+            };
+
+            match asset {
+                Some(asset) if targets.iter().any(|(id, _)| id == &asset.id) => {
+                    return Err(holdings_error(
+                        "duplicate_holding",
+                        format!("Asset {} appears more than once", asset.id),
+                    ));
+                }
+                Some(asset) => targets.push((asset.id, entry.current_amount)),
+                None => unknown.push(
+                    entry
+                        .asset_master_id
+                        .or(entry.ticker_symbol)
+                        .unwrap_or_default(),
+                ),
+            }
+        }
+
+        if !unknown.is_empty() {
+            return Err(holdings_error(
+                "unknown_asset",
+                format!("Unknown assets: {}", unknown.join(", ")),
+            ));
+        }
+
+        Ok(targets)
+    }
+}
+
+fn holdings_error(code: &'static str, message: String) -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        "holdings",
+        ValidationError::new(code).with_message(message.into()),
+    );
+    AppError::Validation(errors)
+}
+
+// --- Allocation Service ---
+
+pub struct AllocationService;
+
+impl AllocationService {
+    /// Aggregates holdings into categories, splitting each holding by its grouping weights.
+    /// Liabilities are reported on their own instead of being netted against the categories.
+    pub async fn calculate(pool: &DbPool, account_id: &str) -> AppResult<PortfolioAllocation> {
+        let mut conn = pool.acquire().await?;
+        let categories = AssetCategoryRepository::find_by_account(&mut conn, account_id).await?;
+        let groupings = AssetGroupingRepository::find_by_account(&mut conn, account_id).await?;
+        let assets = AssetRepository::find_by_account(&mut conn, account_id).await?;
+
+        Ok(Self::aggregate(&categories, &groupings, &assets))
+    }
+
+    fn aggregate(
+        categories: &[AssetCategory],
+        groupings: &[UserAssetGrouping],
+        assets: &[Asset],
+    ) -> PortfolioAllocation {
+        let (liabilities, assets): (Vec<&Asset>, Vec<&Asset>) =
+            assets.iter().partition(|asset| asset.is_liability);
+        let total_amount: Decimal = assets.iter().map(|asset| asset.current_amount).sum();
+        let liability_amount: Decimal = liabilities.iter().map(|asset| asset.current_amount).sum();
+
+        let mut category_amounts: HashMap<&str, Decimal> = HashMap::new();
+        let mut unallocated_amount = Decimal::ZERO;
+
+        for asset in assets {
+            let mut remaining = asset.current_amount;
+            for grouping in groupings
+                .iter()
+                .filter(|grouping| grouping.asset_master_id == asset.asset_master_id)
+            {
+                let share = asset.current_amount * grouping.weight / Decimal::ONE_HUNDRED;
+                *category_amounts
+                    .entry(grouping.category_id.as_str())
+                    .or_insert(Decimal::ZERO) += share;
+                remaining -= share;
+            }
+            unallocated_amount += remaining;
+        }
+
+        let categories = categories
+            .iter()
+            .map(|category| {
+                let current_amount = category_amounts
+                    .get(category.id.as_str())
+                    .copied()
+                    .unwrap_or(Decimal::ZERO);
+                let current_ratio = ratio_of(current_amount, total_amount);
+                CategoryAllocation {
+                    category_id: category.id.clone(),
+                    name: category.name.clone(),
+                    target_ratio: category.target_ratio,
+                    current_amount,
+                    current_ratio,
+                    deviation: current_ratio - category.target_ratio,
+                }
+            })
+            .collect();
+
+        PortfolioAllocation {
+            total_amount,
+            categories,
+            unallocated_amount,
+            liability_amount,
+            net_amount: total_amount - liability_amount,
+        }
+    }
+}
+
+/// Percentage of `part` in `total`, rounded to 2 decimal places
+fn ratio_of(part: Decimal, total: Decimal) -> Decimal {
+    if total.is_zero() {
+        return Decimal::ZERO;
+    }
+    (part / total * Decimal::ONE_HUNDRED).round_dp(2)
+}
+
+// --- Look-through Service ---
+
+pub struct LookThroughService;
+
+impl LookThroughService {
+    /// Breaks every held asset down by its catalog composition to show the true exposure;
+    /// liabilities are left out
+    pub async fn calculate(
+        pool: &DbPool,
+        account_id: &str,
+        dimension: CompositionDimension,
+    ) -> AppResult<LookThroughReport> {
+        let mut conn = pool.acquire().await?;
+        let assets = AssetRepository::find_by_account(&mut conn, account_id).await?;
+        let compositions =
+            AssetMasterRepository::find_compositions_by_dimension(&mut conn, dimension).await?;
+
+        Ok(Self::aggregate(dimension, &assets, &compositions))
+    }
+
+    fn aggregate(
+        dimension: CompositionDimension,
+        assets: &[Asset],
+        compositions: &[AssetComposition],
+    ) -> LookThroughReport {
+        let assets: Vec<&Asset> = assets.iter().filter(|asset| !asset.is_liability).collect();
+        let total_amount: Decimal = assets.iter().map(|asset| asset.current_amount).sum();
+
+        let mut label_amounts: HashMap<&str, Decimal> = HashMap::new();
+        let mut uncovered_amount = Decimal::ZERO;
+
+        for asset in assets {
+            let mut covered = false;
+            for item in compositions
+                .iter()
+                .filter(|item| item.asset_master_id == asset.asset_master_id)
+            {
+                covered = true;
+                *label_amounts
+                    .entry(item.label.as_str())
+                    .or_insert(Decimal::ZERO) +=
+                    asset.current_amount * item.weight / Decimal::ONE_HUNDRED;
+            }
+            if !covered {
+                uncovered_amount += asset.current_amount;
+            }
+        }
+
+        let mut exposures: Vec<Exposure> = label_amounts
+            .into_iter()
+            .map(|(label, amount)| Exposure {
+                label: label.to_string(),
+                amount,
+                ratio: ratio_of(amount, total_amount),
+            })
+            .collect();
+        exposures.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.label.cmp(&b.label)));
+
+        LookThroughReport {
+            dimension,
+            total_amount,
+            exposures,
+            uncovered_amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::AssetService;
+    use crate::core::database::test_pool;
+    use crate::core::error::AppError;
+    use crate::domains::accounts::service::AccountService;
+    use crate::domains::portfolio::schema::BatchHoldingEntry;
+
+    #[tokio::test]
+    async fn an_asset_is_held_once_per_account() {
+        let pool = test_pool().await;
+        let account = AccountService::create(&pool, "owner".into(), "owner@example.com".into())
+            .await
+            .expect("account is created");
+        sqlx::query("INSERT INTO asset_master (id, name) VALUES ('fund', 'Fund')")
+            .execute(&pool)
+            .await
+            .expect("catalog asset is created");
+        AssetService::create(
+            &pool,
+            &account.id,
+            "fund".into(),
+            Decimal::from(1000),
+            false,
+        )
+        .await
+        .expect("first holding is created");
+
+        let second =
+            AssetService::create(&pool, &account.id, "fund".into(), Decimal::from(500), false)
+                .await;
+        assert!(matches!(second, Err(AppError::Conflict(_))));
+
+        let entries = vec![BatchHoldingEntry {
+            asset_master_id: Some("fund".into()),
+            ticker_symbol: None,
+            current_amount: Decimal::from(2000),
+        }];
+        let diff = AssetService::sync(&pool, &account.id, entries, false, true)
+            .await
+            .expect("dry run succeeds");
+        assert_eq!(diff.updated.len(), 1);
+        assert_eq!(diff.updated[0].previous_amount, Some(Decimal::from(1000)));
     }
 }
@@ -0,0 +1,161 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use validator::Validate;
+
+use crate::core::database::DbPool;
+use crate::core::error::AppResult;
+use crate::domains::rebalance::model::{RebalancePlan, RebalancePlanDetail, RebalancePlanItem};
+use crate::domains::rebalance::schema::{CreatePlanRequest, ExecutePlanItemRequest};
+use crate::domains::rebalance::service::RebalancePlanService;
+use crate::middleware::extractor::{JsonBody, get_account_id};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/rebalance/plans",
+    request_body = CreatePlanRequest,
+    responses(
+        (status = 201, description = "Plan saved successfully", body = RebalancePlanDetail),
+        (status = 400, description = "Bad request"),
+        (status = 422, description = "Unknown catalog asset")
+    ),
+    tag = "rebalance"
+)]
+pub async fn create_plan(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    JsonBody(payload): JsonBody<CreatePlanRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    payload.validate()?;
+    let items = payload
+        .items
+        .into_iter()
+        .map(|item| (item.asset_master_id, item.planned_amount))
+        .collect();
+    let plan =
+        RebalancePlanService::create(&pool, &account_id, payload.kind, payload.note, items).await?;
+    Ok((StatusCode::CREATED, Json(plan)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/rebalance/plans",
+    responses(
+        (status = 200, description = "List all plans", body = [RebalancePlan])
+    ),
+    tag = "rebalance"
+)]
+pub async fn list_plans(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let plans = RebalancePlanService::get_by_account(&pool, &account_id).await?;
+    Ok(Json(plans))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/rebalance/plans/{id}",
+    params(
+        ("id" = String, Path, description = "Plan ID")
+    ),
+    responses(
+        (status = 200, description = "Plan found", body = RebalancePlanDetail),
+        (status = 404, description = "Plan not found")
+    ),
+    tag = "rebalance"
+)]
+pub async fn get_plan(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let plan = RebalancePlanService::get_by_id(&pool, &id, &account_id).await?;
+    Ok(Json(plan))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/rebalance/plans/{id}/items/{item_id}/execution",
+    params(
+        ("id" = String, Path, description = "Plan ID"),
+        ("item_id" = String, Path, description = "Plan item ID")
+    ),
+    request_body = ExecutePlanItemRequest,
+    responses(
+        (status = 200, description = "Item marked executed", body = RebalancePlanItem),
+        (status = 400, description = "Plan already applied or amount too precise"),
+        (status = 404, description = "Plan or item not found")
+    ),
+    tag = "rebalance"
+)]
+pub async fn execute_plan_item(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path((id, item_id)): Path<(String, String)>,
+    JsonBody(payload): JsonBody<ExecutePlanItemRequest>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    payload.validate()?;
+    let item = RebalancePlanService::execute_item(
+        &pool,
+        &id,
+        &item_id,
+        &account_id,
+        payload.executed_amount,
+    )
+    .await?;
+    Ok(Json(item))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/rebalance/plans/{id}/apply",
+    params(
+        ("id" = String, Path, description = "Plan ID")
+    ),
+    responses(
+        (status = 200, description = "Executed amounts applied to holdings", body = RebalancePlanDetail),
+        (status = 400, description = "Plan already applied or sells exceed holdings"),
+        (status = 404, description = "Plan not found")
+    ),
+    tag = "rebalance"
+)]
+pub async fn apply_plan(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    let plan = RebalancePlanService::apply(&pool, &id, &account_id).await?;
+    Ok(Json(plan))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/rebalance/plans/{id}",
+    params(
+        ("id" = String, Path, description = "Plan ID")
+    ),
+    responses(
+        (status = 204, description = "Plan deleted successfully"),
+        (status = 400, description = "Plan already applied"),
+        (status = 404, description = "Plan not found")
+    ),
+    tag = "rebalance"
+)]
+pub async fn delete_plan(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let account_id = get_account_id(&headers)?;
+    RebalancePlanService::delete(&pool, &id, &account_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
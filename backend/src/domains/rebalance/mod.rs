@@ -0,0 +1,30 @@
+pub mod handler;
+pub mod model;
+pub mod repository;
+pub mod schema;
+pub mod service;
+
+use crate::core::database::DbPool;
+
+use axum::{
+    Router,
+    routing::{get, post, put},
+};
+
+pub fn rebalance_routes(pool: DbPool) -> Router {
+    Router::new()
+        .route(
+            "/plans",
+            post(handler::create_plan).get(handler::list_plans),
+        )
+        .route(
+            "/plans/{id}",
+            get(handler::get_plan).delete(handler::delete_plan),
+        )
+        .route(
+            "/plans/{id}/items/{item_id}/execution",
+            put(handler::execute_plan_item),
+        )
+        .route("/plans/{id}/apply", post(handler::apply_plan))
+        .with_state(pool)
+}
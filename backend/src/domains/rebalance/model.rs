@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Row, sqlite::SqliteRow};
+
+use crate::shared::decimal::decode_decimal;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum PlanKind {
+    /// Buy and sell to restore the target ratios
+    Rebalance,
+    /// Distribute additional cash without selling
+    Contribution,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum PlanStatus {
+    Draft,
+    Applied,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, utoipa::ToSchema)]
+pub struct RebalancePlan {
+    pub id: String,
+    pub account_id: String,
+    pub kind: PlanKind,
+    pub status: PlanStatus,
+    pub note: Option<String>,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RebalancePlanItem {
+    pub id: String,
+    pub plan_id: String,
+    pub asset_master_id: String,
+    /// Positive to buy, negative to sell
+    pub planned_amount: Decimal,
+    /// Actual filled amount, set once the trade is executed
+    pub executed_amount: Option<Decimal>,
+    pub executed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> FromRow<'r, SqliteRow> for RebalancePlanItem {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let executed_amount = match row.try_get::<Option<String>, _>("executed_amount")? {
+            Some(_) => Some(decode_decimal(row, "executed_amount")?),
+            None => None,
+        };
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            plan_id: row.try_get("plan_id")?,
+            asset_master_id: row.try_get("asset_master_id")?,
+            planned_amount: decode_decimal(row, "planned_amount")?,
+            executed_amount,
+            executed_at: row.try_get("executed_at")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RebalancePlanDetail {
+    #[serde(flatten)]
+    pub plan: RebalancePlan,
+    pub items: Vec<RebalancePlanItem>,
+}
@@ -0,0 +1,182 @@
+use crate::core::database::DbConn;
+use crate::core::error::{AppError, AppResult};
+use crate::domains::rebalance::model::{PlanKind, PlanStatus, RebalancePlan, RebalancePlanItem};
+use rust_decimal::Decimal;
+use sqlx::query_as;
+
+pub struct RebalancePlanRepository;
+
+impl RebalancePlanRepository {
+    pub async fn create(
+        conn: &mut DbConn,
+        id: &str,
+        account_id: &str,
+        kind: PlanKind,
+        note: Option<String>,
+    ) -> AppResult<RebalancePlan> {
+        sqlx::query(
+            r#"
+            INSERT INTO rebalance_plans (id, account_id, kind, note)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .bind(kind)
+        .bind(note)
+        .execute(&mut *conn)
+        .await?;
+
+        Self::find_by_id(conn, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Created plan not found".into()))
+    }
+
+    pub async fn create_item(
+        conn: &mut DbConn,
+        id: &str,
+        plan_id: &str,
+        asset_master_id: String,
+        planned_amount: Decimal,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO rebalance_plan_items (id, plan_id, asset_master_id, planned_amount)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(plan_id)
+        .bind(asset_master_id)
+        .bind(planned_amount.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_account(
+        conn: &mut DbConn,
+        account_id: &str,
+    ) -> AppResult<Vec<RebalancePlan>> {
+        let plans = query_as::<_, RebalancePlan>(
+            r#"
+            SELECT id, account_id, kind, status, note, applied_at, created_at, updated_at
+            FROM rebalance_plans
+            WHERE account_id = ?
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(account_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(plans)
+    }
+
+    pub async fn find_by_id(conn: &mut DbConn, id: &str) -> AppResult<Option<RebalancePlan>> {
+        let plan = query_as::<_, RebalancePlan>(
+            r#"
+            SELECT id, account_id, kind, status, note, applied_at, created_at, updated_at
+            FROM rebalance_plans
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(plan)
+    }
+
+    pub async fn find_items(conn: &mut DbConn, plan_id: &str) -> AppResult<Vec<RebalancePlanItem>> {
+        let items = query_as::<_, RebalancePlanItem>(
+            r#"
+            SELECT id, plan_id, asset_master_id, planned_amount, executed_amount, executed_at,
+                   created_at, updated_at
+            FROM rebalance_plan_items
+            WHERE plan_id = ?
+            ORDER BY rowid
+            "#,
+        )
+        .bind(plan_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(items)
+    }
+
+    pub async fn execute_item(
+        conn: &mut DbConn,
+        plan_id: &str,
+        item_id: &str,
+        executed_amount: Decimal,
+    ) -> AppResult<RebalancePlanItem> {
+        let result = sqlx::query(
+            r#"
+            UPDATE rebalance_plan_items
+            SET executed_amount = ?, executed_at = CURRENT_TIMESTAMP,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND plan_id = ?
+            "#,
+        )
+        .bind(executed_amount.to_string())
+        .bind(item_id)
+        .bind(plan_id)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Plan item not found".into()));
+        }
+
+        Self::find_items(conn, plan_id)
+            .await?
+            .into_iter()
+            .find(|item| item.id == item_id)
+            .ok_or_else(|| AppError::NotFound("Plan item not found after update".into()))
+    }
+
+    /// Moves a draft plan to applied; false when it was no longer a draft, so only one
+    /// of several concurrent applies gets to change the holdings
+    pub async fn mark_applied(conn: &mut DbConn, id: &str) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE rebalance_plans
+            SET status = ?, applied_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND status = ?
+            "#,
+        )
+        .bind(PlanStatus::Applied)
+        .bind(id)
+        .bind(PlanStatus::Draft)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn delete(conn: &mut DbConn, id: &str, account_id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM rebalance_plan_items WHERE plan_id = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM rebalance_plans
+            WHERE id = ? AND account_id = ?
+            "#,
+        )
+        .bind(id)
+        .bind(account_id)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Plan not found or access denied".into()));
+        }
+
+        Ok(())
+    }
+}
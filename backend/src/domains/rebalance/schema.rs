@@ -0,0 +1,43 @@
+use crate::domains::rebalance::model::PlanKind;
+use crate::shared::validation::{validate_holding_amount, validate_non_blank};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use validator::{Validate, ValidationError};
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct PlanItemRequest {
+    #[validate(custom(
+        function = "validate_non_blank",
+        message = "Asset Master ID cannot be empty or blank"
+    ))]
+    pub asset_master_id: String,
+    /// Positive to buy, negative to sell
+    #[validate(custom(
+        function = "validate_trade_amount",
+        message = "Amount must have at most 4 decimal places"
+    ))]
+    pub planned_amount: Decimal,
+}
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct CreatePlanRequest {
+    pub kind: PlanKind,
+    pub note: Option<String>,
+    #[validate(nested)]
+    pub items: Vec<PlanItemRequest>,
+}
+
+#[derive(Deserialize, Validate, utoipa::ToSchema)]
+pub struct ExecutePlanItemRequest {
+    /// Actual filled amount (positive for buys, negative for sells)
+    #[validate(custom(
+        function = "validate_trade_amount",
+        message = "Amount must have at most 4 decimal places"
+    ))]
+    pub executed_amount: Decimal,
+}
+
+/// Trades may be negative, but what they add to a holding must fit its scale
+fn validate_trade_amount(value: &Decimal) -> Result<(), ValidationError> {
+    validate_holding_amount(&value.abs())
+}
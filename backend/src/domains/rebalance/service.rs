@@ -0,0 +1,269 @@
+use crate::core::database::{DbConn, DbPool, transaction};
+use crate::core::error::{AppError, AppResult};
+use crate::domains::audit::model::AuditEntity;
+use crate::domains::audit::service::AuditService;
+use crate::domains::catalog::repository::AssetMasterRepository;
+use crate::domains::portfolio::repository::AssetRepository;
+use crate::domains::rebalance::model::{
+    PlanKind, PlanStatus, RebalancePlan, RebalancePlanDetail, RebalancePlanItem,
+};
+use crate::domains::rebalance::repository::RebalancePlanRepository;
+use crate::shared::util::generate_id;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+pub struct RebalancePlanService;
+
+impl RebalancePlanService {
+    /// `items` is a list of (asset_master_id, planned_amount)
+    pub async fn create(
+        pool: &DbPool,
+        account_id: &str,
+        kind: PlanKind,
+        note: Option<String>,
+        items: Vec<(String, Decimal)>,
+    ) -> AppResult<RebalancePlanDetail> {
+        let id = generate_id();
+        transaction(pool, async |conn| {
+            ensure_catalog_assets(conn, &items).await?;
+            let plan = RebalancePlanRepository::create(conn, &id, account_id, kind, note).await?;
+            for (asset_master_id, planned_amount) in items {
+                RebalancePlanRepository::create_item(
+                    conn,
+                    &generate_id(),
+                    &id,
+                    asset_master_id,
+                    planned_amount,
+                )
+                .await?;
+            }
+            Self::with_items(conn, plan).await
+        })
+        .await
+    }
+
+    pub async fn get_by_account(pool: &DbPool, account_id: &str) -> AppResult<Vec<RebalancePlan>> {
+        let mut conn = pool.acquire().await?;
+        RebalancePlanRepository::find_by_account(&mut conn, account_id).await
+    }
+
+    pub async fn get_by_id(
+        pool: &DbPool,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<RebalancePlanDetail> {
+        let mut conn = pool.acquire().await?;
+        let plan = Self::find_owned(&mut conn, id, account_id).await?;
+        Self::with_items(&mut conn, plan).await
+    }
+
+    pub async fn execute_item(
+        pool: &DbPool,
+        id: &str,
+        item_id: &str,
+        account_id: &str,
+        executed_amount: Decimal,
+    ) -> AppResult<RebalancePlanItem> {
+        transaction(pool, async |conn| {
+            let plan = Self::find_owned(conn, id, account_id).await?;
+            ensure_draft(&plan)?;
+            RebalancePlanRepository::execute_item(conn, id, item_id, executed_amount).await
+        })
+        .await
+    }
+
+    /// Marks the plan applied and adds every executed amount to the account's holdings,
+    /// all inside one transaction
+    pub async fn apply(
+        pool: &DbPool,
+        id: &str,
+        account_id: &str,
+    ) -> AppResult<RebalancePlanDetail> {
+        transaction(pool, async |conn| {
+            Self::find_owned(conn, id, account_id).await?;
+            // Claim the plan before touching holdings so a concurrent apply cannot add twice
+            if !RebalancePlanRepository::mark_applied(conn, id).await? {
+                return Err(plan_applied_error());
+            }
+
+            let items = RebalancePlanRepository::find_items(conn, id).await?;
+            for item in items {
+                let Some(executed_amount) = item.executed_amount else {
+                    continue;
+                };
+
+                let holding =
+                    AssetRepository::find_by_asset_master(conn, account_id, &item.asset_master_id)
+                        .await?;
+                let current_amount = holding
+                    .as_ref()
+                    .map(|holding| holding.current_amount)
+                    .unwrap_or(Decimal::ZERO);
+                if current_amount + executed_amount < Decimal::ZERO {
+                    return Err(oversell_error(&item.asset_master_id));
+                }
+
+                match holding {
+                    Some(holding) => {
+                        let updated = AssetRepository::update(
+                            conn,
+                            &holding.id,
+                            account_id,
+                            current_amount + executed_amount,
+                            holding.is_liability,
+                            holding.version,
+                        )
+                        .await?;
+                        AuditService::updated(
+                            conn,
+                            Some(account_id),
+                            AuditEntity::Holding,
+                            &holding.id,
+                            Some(&holding),
+                            &updated,
+                        )
+                        .await?;
+                    }
+                    None => {
+                        let created = AssetRepository::create(
+                            conn,
+                            &generate_id(),
+                            account_id,
+                            item.asset_master_id,
+                            executed_amount,
+                            false,
+                        )
+                        .await?;
+                        AuditService::created(
+                            conn,
+                            Some(account_id),
+                            AuditEntity::Holding,
+                            &created.id,
+                            &created,
+                        )
+                        .await?;
+                    }
+                }
+            }
+
+            let plan = Self::find_owned(conn, id, account_id).await?;
+            Self::with_items(conn, plan).await
+        })
+        .await
+    }
+
+    pub async fn delete(pool: &DbPool, id: &str, account_id: &str) -> AppResult<()> {
+        transaction(pool, async |conn| {
+            let plan = Self::find_owned(conn, id, account_id).await?;
+            ensure_draft(&plan)?;
+            RebalancePlanRepository::delete(conn, id, account_id).await
+        })
+        .await
+    }
+
+    async fn find_owned(conn: &mut DbConn, id: &str, account_id: &str) -> AppResult<RebalancePlan> {
+        RebalancePlanRepository::find_by_id(conn, id)
+            .await?
+            .filter(|plan| plan.account_id == account_id)
+            .ok_or_else(|| AppError::NotFound("Plan not found".into()))
+    }
+
+    async fn with_items(conn: &mut DbConn, plan: RebalancePlan) -> AppResult<RebalancePlanDetail> {
+        let items = RebalancePlanRepository::find_items(conn, &plan.id).await?;
+        Ok(RebalancePlanDetail { plan, items })
+    }
+}
+
+fn ensure_draft(plan: &RebalancePlan) -> AppResult<()> {
+    if plan.status != PlanStatus::Draft {
+        return Err(plan_applied_error());
+    }
+    Ok(())
+}
+
+/// Unknown assets are reported as `items[i].asset_master_id` instead of failing on the
+/// foreign key
+async fn ensure_catalog_assets(conn: &mut DbConn, items: &[(String, Decimal)]) -> AppResult<()> {
+    let mut unknown = BTreeMap::new();
+    for (index, (asset_master_id, _)) in items.iter().enumerate() {
+        if AssetMasterRepository::find_by_id(conn, asset_master_id)
+            .await?
+            .is_none()
+        {
+            let mut item = ValidationErrors::new();
+            item.add(
+                "asset_master_id",
+                ValidationError::new("unknown_reference").with_message(
+                    format!("Asset {asset_master_id} does not exist in the catalog").into(),
+                ),
+            );
+            unknown.insert(index, Box::new(item));
+        }
+    }
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    let mut errors = ValidationErrors::new();
+    errors
+        .errors_mut()
+        .insert("items".into(), ValidationErrorsKind::List(unknown));
+    Err(AppError::Unprocessable(errors))
+}
+
+fn plan_applied_error() -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        "status",
+        ValidationError::new("plan_applied").with_message("Plan has already been applied".into()),
+    );
+    AppError::Validation(errors)
+}
+
+fn oversell_error(asset_master_id: &str) -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        "executed_amount",
+        ValidationError::new("oversell").with_message(
+            format!(
+                "Executed sells exceed the holding of asset {}",
+                asset_master_id
+            )
+            .into(),
+        ),
+    );
+    AppError::Validation(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::RebalancePlanService;
+    use crate::core::database::test_pool;
+    use crate::core::error::AppError;
+    use crate::domains::accounts::service::AccountService;
+    use crate::domains::rebalance::model::PlanKind;
+
+    #[tokio::test]
+    async fn a_plan_for_an_unknown_asset_is_refused() {
+        let pool = test_pool().await;
+        let account = AccountService::create(&pool, "owner".into(), "owner@example.com".into())
+            .await
+            .expect("account is created");
+
+        let result = RebalancePlanService::create(
+            &pool,
+            &account.id,
+            PlanKind::Rebalance,
+            None,
+            vec![("missing".into(), Decimal::from(1000))],
+        )
+        .await;
+
+        let Err(AppError::Unprocessable(errors)) = result else {
+            panic!("expected an unprocessable plan");
+        };
+        assert!(errors.errors().contains_key("items"));
+    }
+}
@@ -1,17 +1,41 @@
-use axum::Router;
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    middleware::{from_fn, from_fn_with_state},
+};
 use dotenvy::dotenv;
+use std::net::SocketAddr;
+use std::sync::Arc;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+mod cli;
 mod core;
 mod domains;
 mod middleware;
 mod shared;
 
-use crate::core::database::init_db;
+use crate::core::config::Config;
+use crate::core::database::{close_db, init_db, lock_path};
+use crate::core::error::ProblemResponses;
+use crate::core::mail;
+use crate::core::maintenance;
+use crate::core::metrics;
+use crate::core::shutdown;
+use crate::core::telemetry::init_tracing;
 use crate::domains::accounts::account_routes;
+use crate::domains::accounts::service::AccountService;
+use crate::domains::admin::admin_routes;
+use crate::domains::admin::service::PurgeService;
+use crate::domains::audit::audit_routes;
 use crate::domains::catalog::catalog_routes;
+use crate::domains::health::health_routes;
+use crate::domains::imports::import_routes;
+use crate::domains::metrics::metrics_routes;
 use crate::domains::portfolio::portfolio_routes;
+use crate::domains::rebalance::rebalance_routes;
+use crate::middleware::cors::cors_layer;
+use crate::middleware::rate_limit::RateLimiter;
 
 #[derive(OpenApi)]
 #[openapi(
@@ -20,51 +44,161 @@ use crate::domains::portfolio::portfolio_routes;
         crate::domains::catalog::handler::list_assets,
         crate::domains::catalog::handler::get_asset,
         crate::domains::catalog::handler::update_asset,
+        crate::domains::catalog::handler::patch_asset,
         crate::domains::catalog::handler::delete_asset,
+        crate::domains::catalog::handler::get_composition,
+        crate::domains::catalog::handler::replace_composition,
         crate::domains::accounts::handler::create_account,
         crate::domains::accounts::handler::list_accounts,
         crate::domains::accounts::handler::get_account,
         crate::domains::accounts::handler::update_account,
+        crate::domains::accounts::handler::patch_account,
         crate::domains::accounts::handler::delete_account,
+        crate::domains::accounts::handler::restore_account,
+        crate::domains::accounts::handler::request_email_verification,
+        crate::domains::accounts::handler::confirm_email,
+        crate::domains::accounts::handler::request_account_closure,
+        crate::domains::accounts::handler::close_account,
+        crate::domains::accounts::handler::export_account,
+        crate::domains::accounts::handler::import_account,
+        crate::domains::accounts::handler::create_api_token,
+        crate::domains::accounts::handler::list_api_tokens,
+        crate::domains::accounts::handler::revoke_api_token,
         crate::domains::portfolio::handler::create_category,
         crate::domains::portfolio::handler::list_categories,
         crate::domains::portfolio::handler::get_category,
         crate::domains::portfolio::handler::update_category,
+        crate::domains::portfolio::handler::patch_category,
         crate::domains::portfolio::handler::delete_category,
+        crate::domains::portfolio::handler::restore_category,
         crate::domains::portfolio::handler::create_grouping,
         crate::domains::portfolio::handler::list_groupings,
         crate::domains::portfolio::handler::get_grouping,
         crate::domains::portfolio::handler::update_grouping,
+        crate::domains::portfolio::handler::patch_grouping,
         crate::domains::portfolio::handler::delete_grouping,
+        crate::domains::portfolio::handler::restore_grouping,
+        crate::domains::portfolio::handler::get_grouping_split,
+        crate::domains::portfolio::handler::replace_grouping_split,
+        crate::domains::portfolio::handler::suggest_grouping_split,
         crate::domains::portfolio::handler::create_user_asset,
         crate::domains::portfolio::handler::list_user_assets,
         crate::domains::portfolio::handler::get_user_asset,
         crate::domains::portfolio::handler::update_user_asset,
+        crate::domains::portfolio::handler::patch_user_asset,
         crate::domains::portfolio::handler::delete_user_asset,
+        crate::domains::portfolio::handler::restore_user_asset,
+        crate::domains::portfolio::handler::batch_upsert_user_assets,
+        crate::domains::portfolio::handler::get_allocation,
+        crate::domains::portfolio::handler::get_look_through,
+        crate::domains::rebalance::handler::create_plan,
+        crate::domains::rebalance::handler::list_plans,
+        crate::domains::rebalance::handler::get_plan,
+        crate::domains::rebalance::handler::execute_plan_item,
+        crate::domains::rebalance::handler::apply_plan,
+        crate::domains::rebalance::handler::delete_plan,
+        crate::domains::imports::handler::preview_import,
+        crate::domains::imports::handler::apply_import,
+        crate::domains::admin::handler::list_backups,
+        crate::domains::admin::handler::create_backup,
+        crate::domains::admin::handler::restore_backup,
+        crate::domains::admin::handler::get_maintenance,
+        crate::domains::admin::handler::update_maintenance,
+        crate::domains::admin::handler::purge_deleted,
+        crate::domains::audit::handler::list_audit_events,
+        crate::domains::audit::handler::list_catalog_events,
+        crate::domains::health::handler::liveness,
+        crate::domains::health::handler::readiness,
+        crate::domains::metrics::handler::scrape,
     ),
     components(
         schemas(
+            crate::core::error::ProblemDetails,
+            crate::core::error::FieldError,
+            crate::core::error::ErrorCode,
             crate::domains::catalog::model::AssetMaster,
             crate::domains::catalog::schema::CreateAssetRequest,
             crate::domains::catalog::schema::UpdateAssetRequest,
+            crate::domains::catalog::schema::PatchAssetRequest,
+            crate::domains::catalog::model::AssetComposition,
+            crate::domains::catalog::model::CompositionDimension,
+            crate::domains::catalog::schema::CompositionItem,
+            crate::domains::catalog::schema::ReplaceCompositionRequest,
             crate::domains::accounts::model::Account,
             crate::domains::accounts::schema::CreateAccountRequest,
             crate::domains::accounts::schema::UpdateAccountRequest,
+            crate::domains::accounts::schema::PatchAccountRequest,
+            crate::domains::accounts::schema::ConfirmEmailRequest,
+            crate::domains::accounts::model::AccountExport,
+            crate::domains::accounts::model::AccountImportSummary,
+            crate::domains::accounts::model::OwnedRowCounts,
+            crate::domains::accounts::model::ClosureConfirmation,
+            crate::domains::accounts::model::AccountClosureSummary,
+            crate::domains::accounts::schema::CloseAccountRequest,
+            crate::domains::accounts::model::ApiTokenScope,
+            crate::domains::accounts::model::ApiToken,
+            crate::domains::accounts::model::CreatedApiToken,
+            crate::domains::accounts::schema::CreateApiTokenRequest,
             crate::domains::portfolio::model::AssetCategory,
             crate::domains::portfolio::model::UserAssetGrouping,
             crate::domains::portfolio::model::Asset,
+            crate::domains::portfolio::model::CategoryAllocation,
+            crate::domains::portfolio::model::PortfolioAllocation,
+            crate::domains::portfolio::model::GroupingSuggestion,
+            crate::domains::portfolio::model::Exposure,
+            crate::domains::portfolio::model::LookThroughReport,
             crate::domains::portfolio::schema::CreateCategoryRequest,
             crate::domains::portfolio::schema::UpdateCategoryRequest,
+            crate::domains::portfolio::schema::PatchCategoryRequest,
             crate::domains::portfolio::schema::CreateGroupingRequest,
             crate::domains::portfolio::schema::UpdateGroupingRequest,
+            crate::domains::portfolio::schema::PatchGroupingRequest,
+            crate::domains::portfolio::schema::GroupingSplitItem,
+            crate::domains::portfolio::schema::ReplaceGroupingSplitRequest,
             crate::domains::portfolio::schema::CreateUserAssetRequest,
             crate::domains::portfolio::schema::UpdateUserAssetRequest,
+            crate::domains::portfolio::schema::PatchUserAssetRequest,
+            crate::domains::portfolio::schema::BatchHoldingEntry,
+            crate::domains::portfolio::schema::BatchUpsertHoldingsRequest,
+            crate::domains::portfolio::model::HoldingChange,
+            crate::domains::portfolio::model::HoldingsDiff,
+            crate::domains::rebalance::model::PlanKind,
+            crate::domains::rebalance::model::PlanStatus,
+            crate::domains::rebalance::model::RebalancePlan,
+            crate::domains::rebalance::model::RebalancePlanItem,
+            crate::domains::rebalance::model::RebalancePlanDetail,
+            crate::domains::rebalance::schema::PlanItemRequest,
+            crate::domains::rebalance::schema::CreatePlanRequest,
+            crate::domains::rebalance::schema::ExecutePlanItemRequest,
+            crate::domains::imports::model::Broker,
+            crate::domains::imports::model::StatementLine,
+            crate::domains::imports::model::CatalogMatch,
+            crate::domains::imports::model::ImportPreviewLine,
+            crate::domains::imports::model::ImportPreview,
+            crate::domains::imports::schema::ApplyImportLine,
+            crate::domains::imports::schema::ApplyImportRequest,
+            crate::domains::admin::model::BackupInfo,
+            crate::domains::admin::model::MaintenanceStatus,
+            crate::domains::admin::model::PurgeSummary,
+            crate::domains::audit::model::AuditEntity,
+            crate::domains::audit::model::AuditAction,
+            crate::domains::audit::model::AuditEvent,
+            crate::domains::admin::schema::UpdateMaintenanceRequest,
+            crate::domains::health::model::Liveness,
+            crate::domains::health::model::Readiness,
         )
     ),
+    modifiers(&ProblemResponses),
     tags(
         (name = "catalog", description = "Asset Catalog Management"),
         (name = "accounts", description = "Account Management"),
-        (name = "portfolio", description = "Portfolio Management")
+        (name = "portfolio", description = "Portfolio Management"),
+        (name = "rebalance", description = "Rebalance Plan Management"),
+        (name = "imports", description = "Brokerage Statement Import"),
+        (name = "admin", description = "Backup, Maintenance and Purge"),
+        (name = "audit", description = "Audit Log"),
+        (name = "health", description = "Liveness and Readiness Probes"),
+        (name = "metrics", description = "Prometheus Metrics")
     )
 )]
 struct ApiDoc;
@@ -72,27 +206,148 @@ struct ApiDoc;
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
+    init_tracing();
 
-    let pool = init_db().await;
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            tracing::error!("{err}");
+            std::process::exit(1);
+        }
+    };
+    match lock_path(&config.database) {
+        Ok(path) => maintenance::init(path),
+        Err(err) => {
+            tracing::error!(error = %err, "invalid database url");
+            std::process::exit(1);
+        }
+    }
+    let pool = match init_db(&config.database).await {
+        Ok(pool) => pool,
+        Err(err) => {
+            tracing::error!(error = %err, "failed to open the database");
+            std::process::exit(1);
+        }
+    };
 
-    // TODO: Register other domain routes (accounts, portfolio) when they are implemented
-    let app = Router::new()
-        .nest("/api/v1/catalog", catalog_routes(pool.clone()))
+    // The backup, purge and account settings are used by the CLI commands too
+    crate::domains::admin::service::install(&config.admin);
+    crate::domains::accounts::service::install(&config.accounts);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        let result = cli::run(&pool, &args).await;
+        close_db(&pool).await;
+        if let Err(err) = result {
+            tracing::error!(error = ?err, "command failed");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Err(err) = maintenance::hold_server_lock() {
+        tracing::error!(error = ?err, "cannot start the server");
+        close_db(&pool).await;
+        std::process::exit(1);
+    }
+    match AccountService::normalize_legacy(&pool).await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!(
+            count,
+            "normalized the names and emails of existing accounts"
+        ),
+        Err(err) => {
+            tracing::error!(error = ?err, "cannot start the server");
+            close_db(&pool).await;
+            std::process::exit(1);
+        }
+    }
+    metrics::install();
+    mail::install(&config.mail);
+    PurgeService::spawn_schedule(pool.clone());
+
+    let admin_token: Option<Arc<str>> = config.admin.token.as_deref().map(Arc::from);
+    // Shared by the IP quota outside authentication and the account quota inside it
+    let rate_limiter = RateLimiter::new(config.rate_limit.clone());
+    let mut app = Router::new()
+        .nest(
+            "/api/v1/catalog",
+            catalog_routes(pool.clone(), admin_token.clone()),
+        )
         .nest("/api/v1/accounts", account_routes(pool.clone()))
-        .nest("/api/v1/portfolio", portfolio_routes(pool))
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
+        .nest("/api/v1/portfolio", portfolio_routes(pool.clone()))
+        .nest("/api/v1/rebalance", rebalance_routes(pool.clone()))
+        .nest("/api/v1/imports", import_routes(pool.clone()))
+        .nest(
+            "/api/v1/audit",
+            audit_routes(pool.clone(), admin_token.clone()),
+        )
+        .nest(
+            "/api/v1/admin",
+            admin_routes(pool.clone()).layer(from_fn_with_state(
+                admin_token,
+                crate::middleware::admin::require_admin,
+            )),
+        )
+        .layer(from_fn(crate::middleware::audit::capture_actor))
+        .layer(from_fn_with_state(
+            rate_limiter.clone(),
+            crate::middleware::rate_limit::limit_account_rate,
+        ))
+        .layer(from_fn_with_state(
+            pool.clone(),
+            crate::middleware::auth::authenticate,
+        ))
+        .layer(from_fn(
+            crate::middleware::maintenance::reject_during_maintenance,
+        ))
+        .layer(from_fn_with_state(
+            config.request_timeout(),
+            crate::middleware::timeout::enforce_timeout,
+        ))
+        .layer(from_fn_with_state(
+            rate_limiter,
+            crate::middleware::rate_limit::limit_ip_rate,
+        ))
+        .layer(from_fn_with_state(
+            config.server.json_body_limit_bytes,
+            crate::middleware::body_limit::limit_json_body,
+        ))
+        .layer(DefaultBodyLimit::max(config.server.body_limit_bytes))
+        .layer(from_fn(crate::middleware::metrics::track_request))
+        .layer(from_fn(crate::middleware::trace::trace_request))
+        .layer(from_fn(crate::middleware::request_id::assign_request_id))
+        .layer(cors_layer(config.cors_origins()?));
+    // Probes and the scrape endpoint bypass maintenance mode and the request timeout
+    app = app
+        .merge(health_routes(pool.clone()))
+        .merge(metrics_routes(pool.clone()));
+    if config.server.swagger_ui {
+        app = app
+            .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
+    }
 
-    let addr = "0.0.0.0:8000";
+    let addr = config.bind_address()?;
     let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
-        eprintln!("Failed to bind to address {}: {}", addr, e);
+        tracing::error!(%addr, error = %e, "failed to bind");
         e
     })?;
 
-    println!("Server running on http://localhost:8000");
-    axum::serve(listener, app).await.map_err(|e| {
-        eprintln!("Server error: {}", e);
+    tracing::info!(%addr, "server running");
+    // Connect info feeds the per-IP rate limit
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown::signal())
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "server error");
         e
     })?;
 
+    close_db(&pool).await;
+    tracing::info!("server stopped");
+
     Ok(())
 }
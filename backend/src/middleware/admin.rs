@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::core::error::AppError;
+
+const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// Admits only requests carrying the configured admin token. Without a configured token
+/// the admin API is disabled and answers 403.
+pub async fn require_admin(
+    State(expected): State<Option<Arc<str>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(expected) = expected else {
+        return AppError::Forbidden("Admin API is disabled; set admin.token to enable it".into())
+            .into_response();
+    };
+    let presented = request
+        .headers()
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|val| val.to_str().ok());
+    // Comparing digests keeps the time taken independent of how much of the token matches
+    let matches = presented.is_some_and(|presented| {
+        Sha256::digest(presented.as_bytes()) == Sha256::digest(expected.as_bytes())
+    });
+    if !matches {
+        return AppError::Unauthorized(format!("{ADMIN_TOKEN_HEADER} header missing or wrong"))
+            .into_response();
+    }
+    next.run(request).await
+}
@@ -0,0 +1,25 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::middleware::auth::token_account;
+
+tokio::task_local! {
+    static ACTOR: Option<String>;
+}
+
+/// Makes the request's API token account or X-Account-ID available to the audit log
+/// for the duration of the request. Must run inside `authenticate`.
+pub async fn capture_actor(request: Request, next: Next) -> Response {
+    let actor = token_account().or_else(|| {
+        request
+            .headers()
+            .get("X-Account-ID")
+            .and_then(|val| val.to_str().ok())
+            .map(|s| s.to_string())
+    });
+    ACTOR.scope(actor, next.run(request)).await
+}
+
+/// Actor of the current request; None outside a request (CLI, background jobs)
+pub fn current_actor() -> Option<String> {
+    ACTOR.try_with(|actor| actor.clone()).ok().flatten()
+}
@@ -0,0 +1,87 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, Method, header::AUTHORIZATION},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::core::database::DbPool;
+use crate::core::error::AppError;
+use crate::domains::accounts::model::ApiTokenScope;
+use crate::domains::accounts::service::ApiTokenService;
+
+const PORTFOLIO_PREFIX: &str = "/api/v1/portfolio";
+const HOLDINGS_PREFIX: &str = "/api/v1/portfolio/assets";
+const IMPORTS_PREFIX: &str = "/api/v1/imports";
+const CATALOG_PREFIX: &str = "/api/v1/catalog";
+
+tokio::task_local! {
+    static TOKEN_ACCOUNT: String;
+}
+
+/// Resolves `Authorization: Bearer <token>` to its account for the duration of the request.
+/// Requests without a token fall through to the `X-Account-ID` header, which still grants
+/// full access to that account: scopes restrict what a token can do, not the header.
+/// A token that is unknown, expired or revoked is refused with 401 and one lacking a scope
+/// with 403.
+pub async fn authenticate(State(pool): State<DbPool>, request: Request, next: Next) -> Response {
+    let Some(token) = bearer_token(request.headers()) else {
+        return next.run(request).await;
+    };
+    let api_token = match ApiTokenService::authenticate(&pool, &token).await {
+        Ok(Some(api_token)) => api_token,
+        Ok(None) => {
+            return AppError::Unauthorized("API token is invalid, expired or revoked".into())
+                .into_response();
+        }
+        Err(err) => return err.into_response(),
+    };
+
+    let claimed = request
+        .headers()
+        .get("X-Account-ID")
+        .and_then(|val| val.to_str().ok());
+    if claimed.is_some_and(|claimed| claimed != api_token.account_id) {
+        return AppError::Forbidden("X-Account-ID does not match the API token's account".into())
+            .into_response();
+    }
+    if !api_token.scopes.is_empty()
+        && !scopes_allow(&api_token.scopes, request.method(), request.uri().path())
+    {
+        return AppError::Forbidden("API token scopes do not cover this request".into())
+            .into_response();
+    }
+
+    TOKEN_ACCOUNT
+        .scope(api_token.account_id, next.run(request))
+        .await
+}
+
+/// Account of the API token the current request authenticated with, if any
+pub fn token_account() -> Option<String> {
+    TOKEN_ACCOUNT.try_with(|account_id| account_id.clone()).ok()
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then(|| token.trim().to_string())
+}
+
+/// Catalog reads are open to every token; everything not listed here needs an unscoped one
+fn scopes_allow(scopes: &[ApiTokenScope], method: &Method, path: &str) -> bool {
+    let is_read = method == Method::GET || method == Method::HEAD;
+    if is_read && path.starts_with(CATALOG_PREFIX) {
+        return true;
+    }
+    let required = if is_read && path.starts_with(PORTFOLIO_PREFIX) {
+        ApiTokenScope::ReadPortfolio
+    } else if !is_read && (path.starts_with(HOLDINGS_PREFIX) || path.starts_with(IMPORTS_PREFIX)) {
+        ApiTokenScope::WriteHoldings
+    } else {
+        return false;
+    };
+    scopes.contains(&required)
+}
@@ -0,0 +1,51 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::header::{CONTENT_LENGTH, CONTENT_TYPE},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::Limited;
+
+use crate::core::error::AppError;
+
+/// Caps JSON bodies below the general upload limit that statement imports rely on.
+/// A declared `Content-Length` over the limit is refused up front; streamed bodies
+/// are cut off when they reach it.
+pub async fn limit_json_body(State(limit): State<usize>, request: Request, next: Next) -> Response {
+    let is_json = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .is_some_and(is_json_content_type);
+    if !is_json {
+        return next.run(request).await;
+    }
+
+    let declared = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|len| len.parse::<usize>().ok());
+    if declared.is_some_and(|len| len > limit) {
+        return AppError::PayloadTooLarge(format!("JSON body exceeds {limit} bytes"))
+            .into_response();
+    }
+
+    let request = request.map(|body| Body::new(Limited::new(body, limit)));
+    next.run(request).await
+}
+
+/// Matches what axum's `Json` extractor accepts: `application/json` and any
+/// `application/*+json`, case-insensitively and with or without parameters
+fn is_json_content_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence
+        .strip_prefix("application/")
+        .is_some_and(|subtype| subtype == "json" || subtype.ends_with("+json"))
+}
@@ -0,0 +1,28 @@
+use axum::http::{
+    HeaderName, HeaderValue, Method,
+    header::{AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH, RETRY_AFTER},
+};
+use tower_http::cors::CorsLayer;
+
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+
+/// Lets the Web UI on `origins` call the API; no origin is allowed when the list is empty
+pub fn cors_layer(origins: Vec<HeaderValue>) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
+        .allow_headers([
+            AUTHORIZATION,
+            CONTENT_TYPE,
+            IF_MATCH,
+            HeaderName::from_static("x-account-id"),
+            REQUEST_ID_HEADER.clone(),
+        ])
+        .expose_headers([ETAG, RETRY_AFTER, REQUEST_ID_HEADER.clone()])
+}
@@ -1,11 +1,71 @@
 use crate::core::error::{AppError, AppResult};
-use axum::http::HeaderMap;
+use crate::middleware::auth::token_account;
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::{HeaderMap, StatusCode};
+use validator::{ValidationError, ValidationErrors};
 
-/// Helper to extract account_id from headers
+/// Helper to extract account_id from an API token or, failing that, the headers
 pub fn get_account_id(headers: &HeaderMap) -> AppResult<String> {
+    if let Some(account_id) = token_account() {
+        return Ok(account_id);
+    }
     headers
         .get("X-Account-ID")
         .and_then(|val| val.to_str().ok())
         .map(|s| s.to_string())
-        .ok_or_else(|| AppError::NotFound("X-Account-ID header missing".into()))
+        .ok_or_else(|| AppError::Unauthorized("X-Account-ID header or API token missing".into()))
+}
+
+/// Rejects callers acting for another account than `account_id`
+pub fn ensure_account(headers: &HeaderMap, account_id: &str) -> AppResult<()> {
+    if get_account_id(headers)? != account_id {
+        return Err(AppError::Forbidden(
+            "Only the account itself may do this".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// `Json` extractor whose rejections are problem+json errors like every other response:
+/// malformed JSON is a 400 `bad_request`, missing or mistyped fields a 400 `validation_failed`
+pub struct JsonBody<T>(pub T);
+
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(req, state).await {
+            Ok(Json(value)) => Ok(JsonBody(value)),
+            Err(rejection) => Err(json_rejection(rejection)),
+        }
+    }
+}
+
+fn json_rejection(rejection: JsonRejection) -> AppError {
+    let detail = rejection.body_text();
+    match rejection {
+        JsonRejection::JsonDataError(_) => {
+            // serde names the offending path, e.g. `items[0].planned_amount: invalid type`
+            let message = detail
+                .strip_prefix("Failed to deserialize the JSON body into the target type: ")
+                .unwrap_or(&detail)
+                .to_string();
+            let mut errors = ValidationErrors::new();
+            errors.add(
+                "__all__",
+                ValidationError::new("invalid_body").with_message(message.into()),
+            );
+            AppError::Validation(errors)
+        }
+        _ if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            AppError::PayloadTooLarge(detail)
+        }
+        _ => AppError::BadRequest(detail),
+    }
 }
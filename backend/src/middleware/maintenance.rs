@@ -0,0 +1,19 @@
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::core::error::AppError;
+use crate::core::maintenance;
+
+const ADMIN_PREFIX: &str = "/api/v1/admin";
+
+/// Answers 503 for everything but the admin API while maintenance mode is on
+pub async fn reject_during_maintenance(request: Request, next: Next) -> Response {
+    if maintenance::is_enabled() && !request.uri().path().starts_with(ADMIN_PREFIX) {
+        return AppError::ServiceUnavailable("Service is in maintenance mode".into())
+            .into_response();
+    }
+    next.run(request).await
+}
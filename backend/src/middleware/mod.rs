@@ -1 +1,12 @@
+pub mod admin;
+pub mod audit;
+pub mod auth;
+pub mod body_limit;
+pub mod cors;
 pub mod extractor;
+pub mod maintenance;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
+pub mod timeout;
+pub mod trace;
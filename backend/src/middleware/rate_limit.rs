@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::core::config::RateLimitConfig;
+use crate::core::error::AppError;
+use crate::middleware::auth::token_account;
+
+/// Routes that draw from the stricter expensive quota on top of the regular ones;
+/// simulations belong here once they exist
+const EXPENSIVE_PREFIXES: [&str; 1] = ["/api/v1/imports"];
+
+/// Idle buckets are dropped once the table grows past this many clients
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Client {
+    Ip(IpAddr),
+    /// Account an API token authenticated
+    Account(String),
+    /// Unauthenticated `X-Account-ID` claim, kept apart per IP so that sending someone
+    /// else's ID cannot drain their quota
+    ClaimedAccount(String, IpAddr),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Tier {
+    Regular,
+    Expensive,
+}
+
+/// Token bucket holding up to a minute's quota, refilled continuously
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Per-IP and per-account token buckets shared by every request
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Arc<Mutex<HashMap<(Client, Tier), Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Takes a token from every bucket the client draws from, or returns the
+    /// seconds until the emptiest of them has one again
+    fn acquire(&self, clients: &[Client], expensive: bool) -> Result<(), u64> {
+        let mut quotas = Vec::new();
+        for client in clients {
+            let regular = match client {
+                Client::Ip(_) => self.config.per_ip_per_minute,
+                Client::Account(_) | Client::ClaimedAccount(..) => {
+                    self.config.per_account_per_minute
+                }
+            };
+            quotas.push(((client.clone(), Tier::Regular), regular));
+            if expensive {
+                quotas.push((
+                    (client.clone(), Tier::Expensive),
+                    self.config.expensive_per_minute,
+                ));
+            }
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated).as_secs() < 60);
+        }
+
+        let mut wait_secs = 0.0_f64;
+        for (key, quota) in &quotas {
+            let capacity = f64::from(*quota);
+            let per_sec = capacity / 60.0;
+            let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+            });
+            let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * per_sec).min(capacity);
+            bucket.updated = now;
+            if bucket.tokens < 1.0 {
+                wait_secs = wait_secs.max((1.0 - bucket.tokens) / per_sec);
+            }
+        }
+        if wait_secs > 0.0 {
+            return Err(wait_secs.ceil() as u64);
+        }
+
+        for (key, _) in &quotas {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Answers 429 with `Retry-After` once the caller's IP runs out of tokens. Runs outside
+/// authentication so floods of bad tokens are limited too. The IP comes from the
+/// connection, so the router must be served with connect info.
+pub async fn limit_ip_rate(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !limiter.config.enabled {
+        return next.run(request).await;
+    }
+    let Some(ip) = client_ip(&request) else {
+        return next.run(request).await;
+    };
+    let expensive = is_expensive(request.uri().path());
+
+    match limiter.acquire(&[Client::Ip(ip)], expensive) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => AppError::RateLimited(retry_after).into_response(),
+    }
+}
+
+/// Answers 429 with `Retry-After` once the caller's account runs out of tokens. Must run
+/// inside `authenticate` so token clients are keyed on the account their token proves.
+pub async fn limit_account_rate(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !limiter.config.enabled {
+        return next.run(request).await;
+    }
+    let client = match token_account() {
+        Some(account_id) => Some(Client::Account(account_id)),
+        None => request
+            .headers()
+            .get("X-Account-ID")
+            .and_then(|val| val.to_str().ok())
+            .zip(client_ip(&request))
+            .map(|(account_id, ip)| Client::ClaimedAccount(account_id.to_string(), ip)),
+    };
+    let Some(client) = client else {
+        return next.run(request).await;
+    };
+    let expensive = is_expensive(request.uri().path());
+
+    match limiter.acquire(&[client], expensive) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => AppError::RateLimited(retry_after).into_response(),
+    }
+}
+
+fn client_ip(request: &Request) -> Option<IpAddr> {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+fn is_expensive(path: &str) -> bool {
+    EXPENSIVE_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::core::error::AppError;
+
+/// Answers 503 when the handler takes longer than the configured request timeout
+pub async fn enforce_timeout(
+    State(limit): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match tokio::time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => AppError::ServiceUnavailable(format!(
+            "Request timed out after {} seconds",
+            limit.as_secs()
+        ))
+        .into_response(),
+    }
+}
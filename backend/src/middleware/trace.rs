@@ -0,0 +1,36 @@
+use std::time::Instant;
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use tracing::{Instrument, field::Empty};
+
+use crate::middleware::request_id::current_request_id;
+
+/// Runs the request in a span carrying its ID, method and path, and logs the
+/// status and latency once the response is ready. Must run inside `assign_request_id`.
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let span = tracing::info_span!(
+        "request",
+        request_id = current_request_id().unwrap_or_default(),
+        method = %request.method(),
+        path = %request.uri().path(),
+        status = Empty,
+        latency_ms = Empty,
+    );
+
+    let started = Instant::now();
+    let response = next.run(request).instrument(span.clone()).await;
+    let status = response.status();
+    span.record("status", status.as_u16());
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+
+    span.in_scope(|| {
+        if status.is_server_error() {
+            tracing::error!("request failed");
+        } else if status.is_client_error() {
+            tracing::warn!("request rejected");
+        } else {
+            tracing::info!("request completed");
+        }
+    });
+    response
+}
@@ -0,0 +1,15 @@
+use rust_decimal::Decimal;
+use sqlx::{Row, sqlite::SqliteRow};
+use std::str::FromStr;
+
+/// Decimal columns are stored as TEXT to keep their exact precision.
+pub fn decode_decimal(row: &SqliteRow, column: &str) -> Result<Decimal, sqlx::Error> {
+    let raw: String = row.try_get(column)?;
+    let raw = raw.trim();
+    Decimal::from_str(raw)
+        .or_else(|_| Decimal::from_scientific(raw))
+        .map_err(|e| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(e),
+        })
+}
@@ -0,0 +1,49 @@
+use axum::http::{
+    HeaderMap, HeaderName, HeaderValue,
+    header::{ETAG, IF_MATCH},
+};
+use serde::Serialize;
+
+use crate::core::error::{AppError, AppResult};
+
+/// `ETag` response header for an entity version, e.g. `"3"`
+pub fn etag(version: i64) -> [(HeaderName, HeaderValue); 1] {
+    let value = HeaderValue::from_str(&format!("\"{version}\"")).expect("version is ASCII");
+    [(ETAG, value)]
+}
+
+/// Version expected by the client's `If-Match` header; None when absent or `*`
+pub fn parse_if_match(headers: &HeaderMap) -> AppResult<Option<i64>> {
+    let Some(value) = headers.get(IF_MATCH) else {
+        return Ok(None);
+    };
+
+    let raw = value.to_str().unwrap_or_default().trim();
+    if raw == "*" {
+        return Ok(None);
+    }
+    raw.strip_prefix("W/")
+        .unwrap_or(raw)
+        .strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .and_then(|tag| tag.parse().ok())
+        .map(Some)
+        .ok_or_else(|| AppError::BadRequest("If-Match must be an ETag returned by this API".into()))
+}
+
+/// Fails with 412 and the current state when the client edited a stale version
+pub fn ensure_version<T: Serialize>(
+    expected: Option<i64>,
+    version: i64,
+    current: &T,
+) -> AppResult<()> {
+    match expected {
+        Some(expected) if expected != version => Err(precondition_failed(current)),
+        _ => Ok(()),
+    }
+}
+
+/// 412 carrying the entity's current state, for writes that lost a race to another one
+pub fn precondition_failed<T: Serialize>(current: &T) -> AppError {
+    AppError::PreconditionFailed(serde_json::to_value(current).unwrap_or_default())
+}
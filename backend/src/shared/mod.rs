@@ -1,2 +1,4 @@
+pub mod decimal;
+pub mod etag;
 pub mod util;
 pub mod validation;
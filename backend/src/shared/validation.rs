@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use validator::ValidationError;
 
 pub fn validate_non_blank(value: &str) -> Result<(), ValidationError> {
@@ -6,3 +7,88 @@ pub fn validate_non_blank(value: &str) -> Result<(), ValidationError> {
     }
     Ok(())
 }
+
+/// Accepts a weight in the range (0, 100]
+pub fn validate_weight(value: &Decimal) -> Result<(), ValidationError> {
+    if *value <= Decimal::ZERO || *value > Decimal::ONE_HUNDRED {
+        return Err(ValidationError::new("weight_range"));
+    }
+    Ok(())
+}
+
+/// Accepts a target ratio in [0, 100] with at most 2 decimal places, the scale of DECIMAL(5, 2)
+pub fn validate_target_ratio(value: &Decimal) -> Result<(), ValidationError> {
+    if *value < Decimal::ZERO || *value > Decimal::ONE_HUNDRED {
+        return Err(ValidationError::new("ratio_range"));
+    }
+    if value.normalize().scale() > 2 {
+        return Err(ValidationError::new("ratio_scale"));
+    }
+    Ok(())
+}
+
+/// Accepts a non-negative amount with at most 4 decimal places, the scale of DECIMAL(19, 4)
+pub fn validate_holding_amount(value: &Decimal) -> Result<(), ValidationError> {
+    if value.is_sign_negative() && !value.is_zero() {
+        return Err(ValidationError::new("amount_negative"));
+    }
+    if value.normalize().scale() > 4 {
+        return Err(ValidationError::new("amount_scale"));
+    }
+    Ok(())
+}
+
+/// Uppercase letters and digits with an optional exchange suffix, e.g. `VTI`, `7203.T`, `BRK.B`
+pub fn validate_ticker_symbol(value: &str) -> Result<(), ValidationError> {
+    let (symbol, exchange) = match value.split_once('.') {
+        Some((symbol, exchange)) => (symbol, Some(exchange)),
+        None => (value, None),
+    };
+    let is_code = |part: &str, max_len: usize, allow_digits: bool| {
+        (1..=max_len).contains(&part.len())
+            && part
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || (allow_digits && c.is_ascii_digit()))
+    };
+    if !is_code(symbol, 10, true) || exchange.is_some_and(|exchange| !is_code(exchange, 4, false)) {
+        return Err(ValidationError::new("ticker_format"));
+    }
+    Ok(())
+}
+
+/// ISO 6166: country code, nine alphanumerics and a Luhn check digit over the letters as numbers
+pub fn validate_isin(value: &str) -> Result<(), ValidationError> {
+    let bytes = value.as_bytes();
+    let well_formed = bytes.len() == 12
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && bytes[2..11]
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && bytes[11].is_ascii_digit();
+    if !well_formed {
+        return Err(ValidationError::new("isin_format"));
+    }
+
+    // Letters expand to two digits (A = 10 ... Z = 35) before the Luhn check
+    let digits: String = value
+        .chars()
+        .map(|c| c.to_digit(36).unwrap_or_default().to_string())
+        .collect();
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| {
+            if i % 2 == 1 {
+                (d * 2) / 10 + (d * 2) % 10
+            } else {
+                d
+            }
+        })
+        .sum();
+    if !sum.is_multiple_of(10) {
+        return Err(ValidationError::new("isin_checksum"));
+    }
+    Ok(())
+}